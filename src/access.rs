@@ -0,0 +1,190 @@
+//! Clients whose type says which APIs they can call, so calling a trade API
+//! method on a client without credentials doesn't compile.
+
+use crate::client::{
+    AssetFees, AssetNetwork, Client, ClientBuilder, DayBoundary, DaySummary, Error,
+    PublicOrderbook, PublicTrade, Resolution, Symbol, Ticker, TickerResponse,
+};
+use crate::credentials::Credentials;
+use crate::currency::Currency;
+use crate::v4::Candle;
+use chrono::{DateTime, NaiveDate, Utc};
+use std::collections::HashMap;
+use std::ops::Deref;
+
+/// Public: A client of the public data API only
+pub struct PublicClient {
+    client: Client,
+}
+
+/// Public: A client with credentials for the trade API and a public url.
+/// It derefs to `Client`, every method of which it can call.
+pub struct AuthenticatedClient {
+    client: Client,
+}
+
+impl ClientBuilder {
+    /// Public: Fails with `ClientNotConfigured` when the public url isn't set
+    pub fn build_public(self) -> Result<PublicClient, Error> {
+        let client = self.build()?;
+        if !client.has_public_url() {
+            return Err(Error::ClientNotConfigured("public url".to_string()));
+        }
+        Ok(PublicClient { client })
+    }
+
+    /// Public: Fails with `ClientNotConfigured` when the public url, the
+    /// private url or the credentials aren't set. The public url is required
+    /// since the client derefs to `Client`, public data methods included.
+    pub fn build_authenticated(self) -> Result<AuthenticatedClient, Error> {
+        let client = self.build()?;
+        if !client.is_authenticated() {
+            return Err(Error::ClientNotConfigured(
+                "private url and credentials".to_string(),
+            ));
+        }
+        if !client.has_public_url() {
+            return Err(Error::ClientNotConfigured("public url".to_string()));
+        }
+        Ok(AuthenticatedClient { client })
+    }
+}
+
+impl PublicClient {
+    /// Public: Function used to initialize a client of the public API at `url`
+    pub fn new(url: String) -> Self {
+        PublicClient {
+            client: ClientBuilder::new().with_public_url(url).build().unwrap(),
+        }
+    }
+
+    pub async fn ticker(&self, currency: &Currency) -> Result<TickerResponse, Error> {
+        self.client.ticker(currency).await
+    }
+
+    pub async fn tickers(
+        &self,
+        currencies: &[Currency],
+    ) -> Result<HashMap<Currency, Ticker>, Error> {
+        self.client.tickers(currencies).await
+    }
+
+    pub async fn day_summary(
+        &self,
+        currency: &Currency,
+        date: NaiveDate,
+    ) -> Result<DaySummary, Error> {
+        self.client.day_summary(currency, date).await
+    }
+
+    pub async fn day_summaries(
+        &self,
+        currency: &Currency,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<DaySummary>, Error> {
+        self.client.day_summaries(currency, from, to).await
+    }
+
+    pub async fn day_summary_at(
+        &self,
+        currency: &Currency,
+        instant: DateTime<Utc>,
+        boundary: DayBoundary,
+    ) -> Result<DaySummary, Error> {
+        self.client
+            .day_summary_at(currency, instant, boundary)
+            .await
+    }
+
+    pub async fn trades(&self, currency: &Currency) -> Result<Vec<PublicTrade>, Error> {
+        self.client.trades(currency).await
+    }
+
+    pub async fn trades_since(
+        &self,
+        currency: &Currency,
+        tid: i64,
+    ) -> Result<Vec<PublicTrade>, Error> {
+        self.client.trades_since(currency, tid).await
+    }
+
+    pub async fn trades_between(
+        &self,
+        currency: &Currency,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<PublicTrade>, Error> {
+        self.client.trades_between(currency, from, to).await
+    }
+
+    pub async fn orderbook(&self, currency: &Currency) -> Result<PublicOrderbook, Error> {
+        self.client.public_orderbook(currency).await
+    }
+
+    pub async fn network_fees(
+        &self,
+        asset: &Currency,
+        network: Option<&str>,
+    ) -> Result<AssetFees, Error> {
+        self.client.network_fees(asset, network).await
+    }
+
+    pub async fn asset_networks(&self, asset: &Currency) -> Result<Vec<AssetNetwork>, Error> {
+        self.client.asset_networks(asset).await
+    }
+
+    pub async fn asset_fees(&self, asset: &Currency) -> Result<Vec<AssetFees>, Error> {
+        self.client.asset_fees(asset).await
+    }
+
+    pub async fn candles(
+        &self,
+        symbol: &str,
+        resolution: Resolution,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<Candle>, Error> {
+        self.client.candles(symbol, resolution, from, to).await
+    }
+
+    pub async fn symbols(&self) -> Result<Vec<Symbol>, Error> {
+        self.client.symbols().await
+    }
+}
+
+impl AuthenticatedClient {
+    /// Public: Function used to initialize a client of both APIs
+    pub fn new(
+        public_url: String,
+        private_url: String,
+        identifier: String,
+        secret: String,
+    ) -> Self {
+        ClientBuilder::new()
+            .with_public_url(public_url)
+            .with_private_url(private_url)
+            .with_credentials(Credentials::new(identifier, secret))
+            .build_authenticated()
+            .unwrap()
+    }
+
+    /// Public: A client of the public data API sharing this one's settings
+    pub fn public(&self) -> PublicClient {
+        PublicClient {
+            client: self.client.clone(),
+        }
+    }
+
+    pub fn into_inner(self) -> Client {
+        self.client
+    }
+}
+
+impl Deref for AuthenticatedClient {
+    type Target = Client;
+
+    fn deref(&self) -> &Client {
+        &self.client
+    }
+}
@@ -0,0 +1,77 @@
+use crate::client::{Client, Error};
+use std::collections::HashMap;
+use std::future::Future;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::{sleep_until, Instant};
+
+struct Account {
+    client: Client,
+    min_interval: Duration,
+    /// Held for the whole of a call, so calls of an account never overlap
+    next_call: Mutex<Instant>,
+}
+
+/// Public: Clients with different credentials, e.g. trading and custody
+/// keys, addressed by a label.
+///
+/// Calls made with `call` are sequenced per account: one at a time, so
+/// their nonces reach the exchange in the order they were generated, and
+/// spaced to stay within the account's calls per minute. The limit counts
+/// calls, not requests: a call that pages through a listing sends several
+/// requests but takes one slot. Accounts don't wait for each other.
+#[derive(Default)]
+pub struct Accounts {
+    accounts: HashMap<String, Account>,
+}
+
+impl Accounts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Public: Register `client` as `label`, starting at most
+    /// `calls_per_minute` calls through `call`
+    pub fn with_account(mut self, label: &str, client: Client, calls_per_minute: u32) -> Self {
+        let account = Account {
+            client,
+            min_interval: Duration::from_secs(60) / calls_per_minute.max(1),
+            next_call: Mutex::new(Instant::now()),
+        };
+        self.accounts.insert(label.to_string(), account);
+        self
+    }
+
+    pub fn labels(&self) -> impl Iterator<Item = &str> {
+        self.accounts.keys().map(String::as_str)
+    }
+
+    /// The client registered as `label`, bypassing the sequencing and rate
+    /// limit, e.g. for public data
+    pub fn client(&self, label: &str) -> Result<&Client, Error> {
+        Ok(&self.account(label)?.client)
+    }
+
+    /// Run `f` with the client registered as `label` once the account's
+    /// previous call completed and its calls per minute allow, e.g.
+    /// `accounts.call("trading", |client| client.get_account_info()).await`
+    pub async fn call<'a, F, Fut, T>(&'a self, label: &str, f: F) -> Result<T, Error>
+    where
+        F: FnOnce(&'a Client) -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        let account = self.account(label)?;
+        let mut next_call = account.next_call.lock().await;
+
+        sleep_until(*next_call).await;
+        *next_call = Instant::now() + account.min_interval;
+
+        f(&account.client).await
+    }
+
+    fn account(&self, label: &str) -> Result<&Account, Error> {
+        self.accounts
+            .get(label)
+            .ok_or_else(|| Error::ClientNotConfigured(format!("{} account", label)))
+    }
+}
@@ -0,0 +1,421 @@
+//! A client for scripts and cron jobs that don't run an async runtime,
+//! with the same methods as `mb::Client` blocking until they complete.
+//!
+//! Requests go through the async client rather than `reqwest::blocking`, so
+//! transports, middlewares and signers behave the same in both. The
+//! background task of `cancel_all_after` only runs while another call
+//! blocks, so it's left to the async client.
+
+use crate::client::{
+    AccountInfoResponse, AssetFees, AssetNetwork, CancelReport, ClientBuilder, DayBoundary,
+    DaySummary, Error, ListOrdersFilter, ListOrdersResponse, Order, OrderResponse, OrderType,
+    OrderbookResponse, PendingOrder, Placement, PublicOrderbook, PublicTrade, Resolution,
+    SignedRequest, Symbol, Ticker, TickerResponse, WithdrawalEstimate,
+};
+use crate::credentials::Credentials;
+use crate::currency::Currency;
+use crate::rules::{Rule, RuleOutcome};
+use crate::v4::Candle;
+use crate::ws::LiveOrderbook;
+use crate::{
+    AccountSnapshot, ConversionOptions, ConversionReceipt, DaySummaryCache, DuplicateGroup,
+    DuplicateResolution, Portfolio, PortfolioValuation, QueueEstimate, RecentTrades, SafeModeEvent,
+    SafeModePolicy,
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use futures::TryStreamExt;
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::runtime::{Builder, Runtime};
+
+/// Public: Wraps a `mb::Client`, running each call to completion on a
+/// runtime of its own, the same way `reqwest::blocking` does. Its methods
+/// must not be called from within an async runtime.
+pub struct Client {
+    client: crate::Client,
+    runtime: Runtime,
+}
+
+impl Client {
+    /// Public: Block on the calls of `client`
+    pub fn new(client: crate::Client) -> Result<Self, Error> {
+        let runtime = Builder::new_current_thread().enable_all().build()?;
+        Ok(Client { client, runtime })
+    }
+
+    /// Public: Function used to initialize a client that can make requests to
+    /// both public and private APIs
+    #[deprecated(since = "0.1.0", note = "use `ClientBuilder::build_blocking`")]
+    pub fn init(
+        public_url: String,
+        private_url: String,
+        identifier: String,
+        secret: String,
+    ) -> Result<Self, Error> {
+        ClientBuilder::new()
+            .with_public_url(public_url)
+            .with_private_url(private_url)
+            .with_credentials(Credentials::new(identifier, secret))
+            .build_blocking()
+    }
+
+    /// Public: Function used to initialize client that can only make requests to public APIs
+    #[deprecated(since = "0.1.0", note = "use `ClientBuilder::build_blocking`")]
+    pub fn init_public(url: String) -> Result<Self, Error> {
+        ClientBuilder::new().with_public_url(url).build_blocking()
+    }
+
+    /// Public: Function used to intialize a client that can only make private API requests
+    #[deprecated(since = "0.1.0", note = "use `ClientBuilder::build_blocking`")]
+    pub fn init_private(url: String, identifier: String, secret: String) -> Result<Self, Error> {
+        ClientBuilder::new()
+            .with_private_url(url)
+            .with_credentials(Credentials::new(identifier, secret))
+            .build_blocking()
+    }
+
+    /// The async client the calls are made with
+    pub fn inner(&self) -> &crate::Client {
+        &self.client
+    }
+
+    pub fn call_tapi<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: &[(&str, &str)],
+    ) -> Result<T, Error> {
+        self.runtime.block_on(self.client.call_tapi(method, params))
+    }
+
+    pub fn debug_tapi_request(
+        &self,
+        method: &str,
+        params: &[(&str, &str)],
+        nonce: i64,
+    ) -> Result<SignedRequest, Error> {
+        self.runtime
+            .block_on(self.client.debug_tapi_request(method, params, nonce))
+    }
+
+    pub fn ticker(&self, currency: &Currency) -> Result<TickerResponse, Error> {
+        self.runtime.block_on(self.client.ticker(currency))
+    }
+
+    pub fn tickers(&self, currencies: &[Currency]) -> Result<HashMap<Currency, Ticker>, Error> {
+        self.runtime.block_on(self.client.tickers(currencies))
+    }
+
+    pub fn day_summary(&self, currency: &Currency, date: NaiveDate) -> Result<DaySummary, Error> {
+        self.runtime
+            .block_on(self.client.day_summary(currency, date))
+    }
+
+    pub fn day_summaries(
+        &self,
+        currency: &Currency,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<DaySummary>, Error> {
+        self.runtime
+            .block_on(self.client.day_summaries(currency, from, to))
+    }
+
+    pub fn day_summary_at(
+        &self,
+        currency: &Currency,
+        instant: DateTime<Utc>,
+        boundary: DayBoundary,
+    ) -> Result<DaySummary, Error> {
+        self.runtime
+            .block_on(self.client.day_summary_at(currency, instant, boundary))
+    }
+
+    pub fn cached_day_summary(
+        &self,
+        cache: &DaySummaryCache,
+        currency: &Currency,
+        date: NaiveDate,
+    ) -> Result<DaySummary, Error> {
+        self.runtime
+            .block_on(self.client.cached_day_summary(cache, currency, date))
+    }
+
+    pub fn value_portfolio(&self, portfolio: &Portfolio) -> Result<PortfolioValuation, Error> {
+        self.runtime
+            .block_on(self.client.value_portfolio(portfolio))
+    }
+
+    pub fn trades(&self, currency: &Currency) -> Result<Vec<PublicTrade>, Error> {
+        self.runtime.block_on(self.client.trades(currency))
+    }
+
+    pub fn trades_since(&self, currency: &Currency, tid: i64) -> Result<Vec<PublicTrade>, Error> {
+        self.runtime
+            .block_on(self.client.trades_since(currency, tid))
+    }
+
+    pub fn trades_between(
+        &self,
+        currency: &Currency,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<PublicTrade>, Error> {
+        self.runtime
+            .block_on(self.client.trades_between(currency, from, to))
+    }
+
+    pub fn public_orderbook(&self, currency: &Currency) -> Result<PublicOrderbook, Error> {
+        self.runtime
+            .block_on(self.client.public_orderbook(currency))
+    }
+
+    pub fn orderbook(&self, coin_pair: String, full: bool) -> Result<OrderbookResponse, Error> {
+        self.runtime
+            .block_on(self.client.orderbook(coin_pair, full))
+    }
+
+    pub fn place_buy_order(
+        &self,
+        quantity: f64,
+        limit_price: f64,
+        coin_pair: String,
+    ) -> Result<OrderResponse, Error> {
+        self.runtime.block_on(
+            self.client
+                .place_buy_order(quantity, limit_price, coin_pair),
+        )
+    }
+
+    pub fn place_sell_order(
+        &self,
+        quantity: f64,
+        limit_price: f64,
+        coin_pair: String,
+    ) -> Result<OrderResponse, Error> {
+        self.runtime.block_on(
+            self.client
+                .place_sell_order(quantity, limit_price, coin_pair),
+        )
+    }
+
+    pub fn place_market_buy_order(
+        &self,
+        coin_pair: String,
+        cost: f64,
+    ) -> Result<OrderResponse, Error> {
+        self.runtime
+            .block_on(self.client.place_market_buy_order(coin_pair, cost))
+    }
+
+    pub fn place_market_sell_order(
+        &self,
+        coin_pair: String,
+        quantity: f64,
+    ) -> Result<OrderResponse, Error> {
+        self.runtime
+            .block_on(self.client.place_market_sell_order(coin_pair, quantity))
+    }
+
+    pub fn place_order_tracked(
+        &self,
+        order_type: OrderType,
+        quantity: f64,
+        limit_price: f64,
+        coin_pair: String,
+    ) -> Result<Placement, Error> {
+        self.runtime.block_on(self.client.place_order_tracked(
+            order_type,
+            quantity,
+            limit_price,
+            coin_pair,
+        ))
+    }
+
+    pub fn resolve_pending_order(&self, pending: PendingOrder) -> Result<Placement, Error> {
+        self.runtime
+            .block_on(self.client.resolve_pending_order(pending))
+    }
+
+    pub fn find_pending_order(&self, pending: &PendingOrder) -> Result<Option<Order>, Error> {
+        self.runtime
+            .block_on(self.client.find_pending_order(pending))
+    }
+
+    pub fn list_orders(
+        &self,
+        coin_pair: String,
+        filter: &ListOrdersFilter,
+    ) -> Result<ListOrdersResponse, Error> {
+        self.runtime
+            .block_on(self.client.list_orders(coin_pair, filter))
+    }
+
+    /// Every order matching the filter, newest first, see
+    /// `mb::Client::list_orders_paged`
+    pub fn list_orders_paged(
+        &self,
+        coin_pair: String,
+        filter: ListOrdersFilter,
+    ) -> Result<Vec<Order>, Error> {
+        self.runtime.block_on(
+            self.client
+                .list_orders_paged(coin_pair, filter)
+                .try_collect(),
+        )
+    }
+
+    pub fn get_order(&self, coin_pair: String, order_id: i64) -> Result<OrderResponse, Error> {
+        self.runtime
+            .block_on(self.client.get_order(coin_pair, order_id))
+    }
+
+    pub fn cancel_order(&self, coin_pair: String, order_id: i64) -> Result<OrderResponse, Error> {
+        self.runtime
+            .block_on(self.client.cancel_order(coin_pair, order_id))
+    }
+
+    pub fn cancel_all_open_orders(&self, coin_pair: String) -> Result<Vec<CancelReport>, Error> {
+        self.runtime
+            .block_on(self.client.cancel_all_open_orders(coin_pair))
+    }
+
+    pub fn wait_for_fill(
+        &self,
+        coin_pair: String,
+        order_id: i64,
+        interval: Duration,
+        timeout: Duration,
+    ) -> Result<Order, Error> {
+        self.runtime.block_on(
+            self.client
+                .wait_for_fill(coin_pair, order_id, interval, timeout),
+        )
+    }
+
+    pub fn find_duplicate_orders(
+        &self,
+        coin_pair: String,
+        window: chrono::Duration,
+    ) -> Result<Vec<DuplicateGroup>, Error> {
+        self.runtime
+            .block_on(self.client.find_duplicate_orders(coin_pair, window))
+    }
+
+    pub fn resolve_duplicates(
+        &self,
+        group: &DuplicateGroup,
+        resolution: DuplicateResolution,
+    ) -> Vec<CancelReport> {
+        self.runtime
+            .block_on(self.client.resolve_duplicates(group, resolution))
+    }
+
+    pub fn queue_position(
+        &self,
+        order_id: i64,
+        book: &LiveOrderbook,
+        trades: &RecentTrades,
+    ) -> Result<QueueEstimate, Error> {
+        self.runtime
+            .block_on(self.client.queue_position(order_id, book, trades))
+    }
+
+    pub fn convert_brl(
+        &self,
+        asset: &Currency,
+        amount: f64,
+        options: &ConversionOptions,
+    ) -> Result<ConversionReceipt, Error> {
+        self.runtime
+            .block_on(self.client.convert_brl(asset, amount, options))
+    }
+
+    pub fn run_rules(
+        &self,
+        currency: &Currency,
+        rules: &[Rule],
+    ) -> Result<Vec<RuleOutcome>, Error> {
+        self.runtime
+            .block_on(self.client.run_rules(currency, rules))
+    }
+
+    /// Blocks until trading resumes, see `mb::Client::run_safe_mode`
+    pub fn run_safe_mode<F>(&self, coin_pairs: &[&str], policy: &SafeModePolicy, notify: F)
+    where
+        F: FnMut(SafeModeEvent),
+    {
+        self.runtime
+            .block_on(self.client.run_safe_mode(coin_pairs, policy, notify))
+    }
+
+    pub fn refresh_exposure(&self, assets: &[Currency]) -> Result<(), Error> {
+        self.runtime.block_on(self.client.refresh_exposure(assets))
+    }
+
+    pub fn snapshot(
+        &self,
+        coin_pair: String,
+        tolerance: chrono::Duration,
+        max_attempts: u32,
+    ) -> Result<AccountSnapshot, Error> {
+        self.runtime
+            .block_on(self.client.snapshot(coin_pair, tolerance, max_attempts))
+    }
+
+    pub fn get_account_info(&self) -> Result<AccountInfoResponse, Error> {
+        self.runtime.block_on(self.client.get_account_info())
+    }
+
+    pub fn network_fees(
+        &self,
+        asset: &Currency,
+        network: Option<&str>,
+    ) -> Result<AssetFees, Error> {
+        self.runtime
+            .block_on(self.client.network_fees(asset, network))
+    }
+
+    pub fn asset_networks(&self, asset: &Currency) -> Result<Vec<AssetNetwork>, Error> {
+        self.runtime.block_on(self.client.asset_networks(asset))
+    }
+
+    pub fn asset_fees(&self, asset: &Currency) -> Result<Vec<AssetFees>, Error> {
+        self.runtime.block_on(self.client.asset_fees(asset))
+    }
+
+    pub fn estimate_withdrawal(
+        &self,
+        asset: &Currency,
+        amount: f64,
+    ) -> Result<WithdrawalEstimate, Error> {
+        self.runtime
+            .block_on(self.client.estimate_withdrawal(asset, amount))
+    }
+
+    pub fn candles(
+        &self,
+        symbol: &str,
+        resolution: Resolution,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<Candle>, Error> {
+        self.runtime
+            .block_on(self.client.candles(symbol, resolution, from, to))
+    }
+
+    pub fn symbols(&self) -> Result<Vec<Symbol>, Error> {
+        self.runtime.block_on(self.client.symbols())
+    }
+
+    pub fn symbol(&self, symbol: &str) -> Result<Option<Symbol>, Error> {
+        self.runtime.block_on(self.client.symbol(symbol))
+    }
+}
+
+impl ClientBuilder {
+    /// Public: Build a blocking client, see `ClientBuilder::build`
+    pub fn build_blocking(self) -> Result<Client, Error> {
+        Client::new(self.build()?)
+    }
+}
@@ -0,0 +1,97 @@
+use crate::client::{Client, DayBoundary, DaySummary, Error};
+use crate::currency::Currency;
+use chrono::{NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::{Path, PathBuf};
+
+const CACHE_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    version: u32,
+    summary: DaySummary,
+}
+
+/// Public: An on-disk store of day summaries, one JSON file per
+/// `<dir>/<currency>/<date>.json`.
+///
+/// Summaries of closed days never change, so they are served from disk
+/// and only missing or still open days are fetched from the API.
+#[derive(Debug, Clone)]
+pub struct DaySummaryCache {
+    dir: PathBuf,
+}
+
+impl DaySummaryCache {
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        DaySummaryCache {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+
+    fn path(&self, currency: &Currency, date: NaiveDate) -> PathBuf {
+        self.dir
+            .join(currency.to_string())
+            .join(format!("{}.json", date.format("%Y-%m-%d")))
+    }
+
+    /// A missing, unreadable or outdated entry is a cache miss
+    async fn get(&self, currency: &Currency, date: NaiveDate) -> Option<DaySummary> {
+        let bytes = tokio::fs::read(self.path(currency, date)).await.ok()?;
+        let entry: CacheEntry = serde_json::from_slice(&bytes).ok()?;
+
+        if entry.version != CACHE_VERSION {
+            return None;
+        }
+        Some(entry.summary)
+    }
+
+    async fn put(
+        &self,
+        currency: &Currency,
+        date: NaiveDate,
+        summary: DaySummary,
+    ) -> Result<DaySummary, Error> {
+        let path = self.path(currency, date);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        let entry = CacheEntry {
+            version: CACHE_VERSION,
+            summary,
+        };
+        let bytes = serde_json::to_vec(&entry).map_err(io::Error::from)?;
+        tokio::fs::write(path, bytes).await?;
+
+        Ok(entry.summary)
+    }
+}
+
+impl Client {
+    /// Fetch a day summary through `cache`. Days that closed (on Sao Paulo
+    /// time, as the API aggregates them) are stored and served locally;
+    /// the current day is always fetched.
+    pub async fn cached_day_summary(
+        &self,
+        cache: &DaySummaryCache,
+        currency: &Currency,
+        date: NaiveDate,
+    ) -> Result<DaySummary, Error> {
+        let closed = date < DayBoundary::SaoPaulo.date_of(Utc::now());
+
+        if closed {
+            if let Some(summary) = cache.get(currency, date).await {
+                return Ok(summary);
+            }
+        }
+
+        let summary = self.day_summary(currency, date).await?;
+        if !closed {
+            return Ok(summary);
+        }
+
+        cache.put(currency, date, summary).await
+    }
+}
@@ -0,0 +1,151 @@
+use crate::client::Error;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const CALENDAR_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HaltKind {
+    /// The API answered with `ApiStatus::TradingHalted`
+    TradingHalted,
+    /// A maintenance window announced by the exchange
+    Maintenance,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Halt {
+    pub kind: HaltKind,
+    pub start: DateTime<Utc>,
+    /// `None` while the halt is still ongoing
+    pub end: Option<DateTime<Utc>>,
+}
+
+impl Halt {
+    fn overlaps(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> bool {
+        let end = self.end.unwrap_or_else(Utc::now);
+        self.start <= to && end >= from
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CalendarFile {
+    version: u32,
+    halts: Vec<Halt>,
+}
+
+/// Public: A record of observed trading halts and maintenance windows,
+/// optionally persisted to a JSON file, used to tell whether a period of
+/// market data was distorted by the exchange being unavailable.
+#[derive(Debug, Default)]
+pub struct HaltCalendar {
+    path: Option<PathBuf>,
+    halts: Vec<Halt>,
+}
+
+impl HaltCalendar {
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// Public: Open a calendar persisted at `path`, creating it on the first
+    /// recorded halt if it doesn't exist yet
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref().to_path_buf();
+
+        let halts = match fs::read(&path) {
+            Ok(bytes) => {
+                let file: CalendarFile = serde_json::from_slice(&bytes).map_err(io::Error::from)?;
+                if file.version != CALENDAR_VERSION {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("unsupported halt calendar version {}", file.version),
+                    )
+                    .into());
+                }
+                file.halts
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(HaltCalendar {
+            path: Some(path),
+            halts,
+        })
+    }
+
+    pub fn halts(&self) -> &[Halt] {
+        &self.halts
+    }
+
+    /// Record that trading was halted at `at`, opening a new halt unless
+    /// one is already ongoing
+    pub fn observe_halt(&mut self, at: DateTime<Utc>) -> Result<(), Error> {
+        if self.ongoing_halt().is_some() {
+            return Ok(());
+        }
+
+        self.halts.push(Halt {
+            kind: HaltKind::TradingHalted,
+            start: at,
+            end: None,
+        });
+        self.save()
+    }
+
+    /// Record that trading was working at `at`, closing the ongoing halt
+    pub fn observe_trading(&mut self, at: DateTime<Utc>) -> Result<(), Error> {
+        match self.ongoing_halt() {
+            Some(halt) => halt.end = Some(at),
+            None => return Ok(()),
+        }
+        self.save()
+    }
+
+    pub fn record_maintenance(
+        &mut self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<(), Error> {
+        self.halts.push(Halt {
+            kind: HaltKind::Maintenance,
+            start,
+            end: Some(end),
+        });
+        self.save()
+    }
+
+    /// Halts and maintenance windows overlapping the period
+    pub fn halts_between(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Vec<&Halt> {
+        self.halts.iter().filter(|h| h.overlaps(from, to)).collect()
+    }
+
+    pub fn was_halted_during(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> bool {
+        self.halts.iter().any(|h| h.overlaps(from, to))
+    }
+
+    fn ongoing_halt(&mut self) -> Option<&mut Halt> {
+        self.halts
+            .iter_mut()
+            .find(|h| h.kind == HaltKind::TradingHalted && h.end.is_none())
+    }
+
+    fn save(&self) -> Result<(), Error> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        let file = CalendarFile {
+            version: CALENDAR_VERSION,
+            halts: self.halts.clone(),
+        };
+        let bytes = serde_json::to_vec_pretty(&file).map_err(io::Error::from)?;
+        fs::write(path, bytes)?;
+
+        Ok(())
+    }
+}
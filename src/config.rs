@@ -0,0 +1,77 @@
+use crate::client::{Client, ClientBuilder, Error};
+use crate::credentials::Credentials;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// A named set of credentials and urls in a config file, e.g.
+///
+/// ```toml
+/// [prod]
+/// tapi_id = "..."
+/// tapi_secret = "..."
+///
+/// [readonly]
+/// public_url = "https://www.mercadobitcoin.net/api"
+/// ```
+///
+/// Profiles without credentials build public only clients, unset urls
+/// default to production.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct Profile {
+    tapi_id: Option<String>,
+    tapi_secret: Option<String>,
+    public_url: Option<String>,
+    tapi_url: Option<String>,
+}
+
+impl ClientBuilder {
+    /// Public: A builder configured from `profile` of a config file, parsed
+    /// as JSON when its extension is `.json` and as TOML otherwise
+    pub fn from_config_file(path: impl AsRef<Path>, profile: &str) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)?;
+
+        let mut profiles: HashMap<String, Profile> = match path.extension() {
+            Some(extension) if extension == "json" => serde_json::from_str(&contents)
+                .map_err(|e| Error::ConfigError(format!("{}: {}", path.display(), e)))?,
+            _ => toml::from_str(&contents)
+                .map_err(|e| Error::ConfigError(format!("{}: {}", path.display(), e)))?,
+        };
+        let profile = profiles.remove(profile).ok_or_else(|| {
+            Error::ConfigError(format!("{}: no profile {}", path.display(), profile))
+        })?;
+
+        let mut builder = Self::production();
+        match (profile.tapi_id, profile.tapi_secret) {
+            (Some(id), Some(secret)) => {
+                builder = builder.with_credentials(Credentials::new(id, secret));
+            }
+            (None, None) => {}
+            _ => {
+                return Err(Error::ConfigError(format!(
+                    "{}: tapi_id and tapi_secret must be set together",
+                    path.display()
+                )))
+            }
+        }
+        if let Some(url) = profile.public_url {
+            builder = builder.with_public_url(url);
+        }
+        if let Some(url) = profile.tapi_url {
+            builder = builder.with_private_url(url);
+        }
+
+        Ok(builder)
+    }
+}
+
+impl Client {
+    /// Public: A client configured from `profile` of a config file, see
+    /// `ClientBuilder::from_config_file`
+    pub fn from_config_file(path: impl AsRef<Path>, profile: &str) -> Result<Self, Error> {
+        ClientBuilder::from_config_file(path, profile)?.build()
+    }
+}
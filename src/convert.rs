@@ -0,0 +1,152 @@
+use crate::client::{Client, Error, OrderStatus, PriceLevel};
+use crate::currency::Currency;
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+
+/// Public: Safety limits and polling settings for `Client::convert_brl`
+#[derive(Debug, Clone)]
+pub struct ConversionOptions {
+    /// Largest accepted difference between the best ask and the average
+    /// price expected from walking the book, as a fraction (0.01 = 1%)
+    pub max_slippage: f64,
+    pub poll_interval: Duration,
+    pub fill_timeout: Duration,
+}
+
+impl Default for ConversionOptions {
+    fn default() -> Self {
+        ConversionOptions {
+            max_slippage: 0.01,
+            poll_interval: Duration::from_millis(500),
+            fill_timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Public: What a conversion actually did
+#[derive(Debug)]
+pub struct ConversionReceipt {
+    pub order_id: i64,
+    pub coin_pair: String,
+    /// Best ask when the conversion was requested
+    pub quoted_price: f64,
+    /// Average price the order was executed at
+    pub effective_price: f64,
+    pub brl_requested: f64,
+    pub brl_spent: f64,
+    /// BRL left over, `brl_requested - brl_spent`
+    pub brl_residual: f64,
+    pub quantity: f64,
+    /// Fee charged by the exchange, in the bought asset
+    pub fee: f64,
+    /// What was credited, `quantity - fee`
+    pub net_quantity: f64,
+    pub requested_at: DateTime<Utc>,
+    pub completed_at: DateTime<Utc>,
+}
+
+/// Average price of spending `cost` BRL against `asks`, `None` when the
+/// book isn't deep enough
+fn average_fill_price(asks: &[PriceLevel], cost: f64) -> Option<f64> {
+    let mut remaining = cost;
+    let mut quantity = 0.0;
+
+    for level in asks {
+        let spent = remaining.min(level.price * level.quantity);
+        quantity += spent / level.price;
+        remaining -= spent;
+
+        if remaining <= 0.0 {
+            return Some(cost / quantity);
+        }
+    }
+
+    None
+}
+
+impl Client {
+    /// Spend `amount` BRL on `asset` with a market order and wait for it to
+    /// complete, returning a receipt of what was executed.
+    ///
+    /// Before placing the order the BRL balance is checked and the order is
+    /// priced against the public orderbook. It is rejected with
+    /// `Error::ConversionRejected` if it would move the price by more than
+    /// `options.max_slippage`.
+    ///
+    /// The order is sent once and never resent. If the exchange answers
+    /// status 432 the account orders are polled for it, as in
+    /// `Client::resolve_pending_order`; when it doesn't show up in time this
+    /// fails with `ApiError(OrderProcessing)` and the order may still be
+    /// created, so check `list_orders` before converting again.
+    pub async fn convert_brl(
+        &self,
+        asset: &Currency,
+        amount: f64,
+        options: &ConversionOptions,
+    ) -> Result<ConversionReceipt, Error> {
+        let requested_at = Utc::now();
+        let coin_pair = format!("BRL{}", asset);
+
+        let available = self.get_account_info().await?.balance.brl.available;
+        if available < amount {
+            return Err(Error::ConversionRejected(format!(
+                "{} BRL available, {} BRL requested",
+                available, amount
+            )));
+        }
+
+        let orderbook = self.public_orderbook(asset).await?;
+        let quoted_price = match orderbook.asks.first() {
+            Some(best_ask) => best_ask.price,
+            None => {
+                return Err(Error::ConversionRejected(format!(
+                    "no asks for {}",
+                    coin_pair
+                )))
+            }
+        };
+        let expected_price = average_fill_price(&orderbook.asks, amount).ok_or_else(|| {
+            Error::ConversionRejected(format!("not enough {} liquidity", coin_pair))
+        })?;
+        if expected_price > quoted_price * (1.0 + options.max_slippage) {
+            return Err(Error::ConversionRejected(format!(
+                "expected price {} exceeds the slippage limit over {}",
+                expected_price, quoted_price
+            )));
+        }
+
+        let order = self
+            .place_market_buy_order(coin_pair.clone(), amount)
+            .await?
+            .order;
+        let order = match order.status {
+            OrderStatus::Open => {
+                self.wait_for_fill(
+                    coin_pair.clone(),
+                    order.order_id,
+                    options.poll_interval,
+                    options.fill_timeout,
+                )
+                .await?
+            }
+            _ => order,
+        };
+
+        let brl_spent = order.executed_quantity * order.executed_price_avg;
+
+        Ok(ConversionReceipt {
+            order_id: order.order_id,
+            coin_pair,
+            quoted_price,
+            effective_price: order.executed_price_avg,
+            brl_requested: amount,
+            brl_spent,
+            brl_residual: amount - brl_spent,
+            quantity: order.executed_quantity,
+            fee: order.fee,
+            net_quantity: order.executed_quantity - order.fee,
+            requested_at,
+            completed_at: Utc::now(),
+        })
+    }
+}
@@ -0,0 +1,93 @@
+use crate::client::{Client, Error};
+use hmac::{Hmac, Mac};
+use sha2::Sha512;
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+
+type HmacSha512 = Hmac<Sha512>;
+
+tokio::task_local! {
+    static ACTING_AS: Credentials;
+}
+
+/// Public: Computes the TAPI-MAC of requests, so the secret can live outside
+/// the process, e.g. in an HSM or a KMS.
+///
+/// `message` is the canonical request: the API path followed by the url
+/// encoded params. Implementations must return the hex encoded
+/// HMAC-SHA512 of it, and report failures as `Error::SignerError`.
+#[async_trait::async_trait]
+pub trait Signer: Send + Sync {
+    async fn sign(&self, message: &str) -> Result<String, Error>;
+}
+
+/// Public: Signs in process with a secret held in memory
+pub struct HmacSigner {
+    secret: String,
+}
+
+impl HmacSigner {
+    pub fn new(secret: String) -> Self {
+        HmacSigner { secret }
+    }
+}
+
+#[async_trait::async_trait]
+impl Signer for HmacSigner {
+    async fn sign(&self, message: &str) -> Result<String, Error> {
+        let mut mac = HmacSha512::new_from_slice(self.secret.as_bytes())
+            .expect("HMAC can take key of any size");
+
+        mac.update(message.as_bytes());
+        Ok(hex::encode(mac.finalize().into_bytes()))
+    }
+}
+
+/// Public: The TAPI id of an account and the signer for its secret
+#[derive(Clone)]
+pub struct Credentials {
+    identifier: String,
+    signer: Arc<dyn Signer>,
+}
+
+impl Credentials {
+    pub fn new(identifier: String, secret: String) -> Self {
+        Self::with_signer(identifier, Arc::new(HmacSigner::new(secret)))
+    }
+
+    pub fn with_signer(identifier: String, signer: Arc<dyn Signer>) -> Self {
+        Credentials { identifier, signer }
+    }
+
+    pub fn identifier(&self) -> &str {
+        &self.identifier
+    }
+
+    pub(crate) async fn sign(&self, message: &str) -> Result<String, Error> {
+        self.signer.sign(message).await
+    }
+}
+
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("identifier", &self.identifier)
+            .finish()
+    }
+}
+
+/// Credentials set with `Client::acting_as` for the current task, if any
+pub(crate) fn acting_as() -> Option<Credentials> {
+    ACTING_AS.try_with(Credentials::clone).ok()
+}
+
+impl Client {
+    /// Public: Run `f` with every TAPI request it makes signed with
+    /// `credentials` instead of the client's own, so one client can act for
+    /// several accounts, e.g.
+    /// `Client::acting_as(credentials, client.get_account_info()).await`
+    pub async fn acting_as<F: Future>(credentials: Credentials, f: F) -> F::Output {
+        ACTING_AS.scope(credentials, f).await
+    }
+}
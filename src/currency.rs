@@ -0,0 +1,66 @@
+use serde::{Deserialize, Deserializer};
+use std::convert::Infallible;
+use std::fmt;
+use std::str::FromStr;
+
+/// Public: Coins traded on Mercado Bitcoin, as used in public API routes.
+/// Listings not covered by a variant can still be named with `Other`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Currency {
+    /// Only meaningful for balances, BRL has no market of its own
+    Brl,
+    Btc,
+    Eth,
+    Ltc,
+    Bch,
+    Xrp,
+    Usdc,
+    Paxg,
+    Other(String),
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let code = match self {
+            Self::Brl => "BRL",
+            Self::Btc => "BTC",
+            Self::Eth => "ETH",
+            Self::Ltc => "LTC",
+            Self::Bch => "BCH",
+            Self::Xrp => "XRP",
+            Self::Usdc => "USDC",
+            Self::Paxg => "PAXG",
+            Self::Other(code) => code,
+        };
+        f.write_str(code)
+    }
+}
+
+impl FromStr for Currency {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let code = s.to_uppercase();
+        Ok(match code.as_str() {
+            "BRL" => Self::Brl,
+            "BTC" => Self::Btc,
+            "ETH" => Self::Eth,
+            "LTC" => Self::Ltc,
+            "BCH" => Self::Bch,
+            "XRP" => Self::Xrp,
+            "USDC" => Self::Usdc,
+            "PAXG" => Self::Paxg,
+            _ => Self::Other(code),
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for Currency {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let code = String::deserialize(deserializer)?;
+        Ok(code.parse().unwrap())
+    }
+}
@@ -0,0 +1,97 @@
+use crate::client::{CancelReport, Client, Error, ListOrdersFilter, Order, OrderStatus};
+use chrono::Duration;
+use futures::TryStreamExt;
+
+/// Open orders that look like copies of each other: same pair, side, price
+/// and quantity, created within a short window. These usually come from a
+/// placement retried after a crash or reconnect.
+#[derive(Debug)]
+pub struct DuplicateGroup {
+    /// Oldest first
+    pub orders: Vec<Order>,
+}
+
+/// Public: Which order of a `DuplicateGroup` survives, the others are cancelled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateResolution {
+    KeepOldest,
+    KeepNewest,
+}
+
+impl DuplicateGroup {
+    /// The orders that `resolution` would cancel
+    pub fn extras(&self, resolution: DuplicateResolution) -> &[Order] {
+        match resolution {
+            DuplicateResolution::KeepOldest => &self.orders[1..],
+            DuplicateResolution::KeepNewest => &self.orders[..self.orders.len() - 1],
+        }
+    }
+}
+
+fn same_order(a: &Order, b: &Order) -> bool {
+    a.coin_pair == b.coin_pair
+        && a.order_type == b.order_type
+        && a.limit_price == b.limit_price
+        && a.quantity == b.quantity
+}
+
+impl Client {
+    /// Scan every page of open orders of a pair for likely duplicates, i.e.
+    /// identical orders each created within `window` of the previous one
+    pub async fn find_duplicate_orders(
+        &self,
+        coin_pair: String,
+        window: Duration,
+    ) -> Result<Vec<DuplicateGroup>, Error> {
+        let filter = ListOrdersFilter {
+            status_list: vec![OrderStatus::Open],
+            ..Default::default()
+        };
+        let mut open_orders: Vec<Order> = self
+            .list_orders_paged(coin_pair, filter)
+            .try_collect()
+            .await?;
+        open_orders.sort_by_key(|order| (order.created_timestamp, order.order_id));
+
+        let mut groups: Vec<Vec<Order>> = Vec::new();
+        for order in open_orders {
+            let group = groups.iter_mut().find(|group| {
+                let last = group.last().unwrap();
+                same_order(last, &order)
+                    && order.created_timestamp - last.created_timestamp <= window
+            });
+
+            match group {
+                Some(group) => group.push(order),
+                None => groups.push(vec![order]),
+            }
+        }
+
+        Ok(groups
+            .into_iter()
+            .filter(|orders| orders.len() > 1)
+            .map(|orders| DuplicateGroup { orders })
+            .collect())
+    }
+
+    /// Cancel the extra orders of a duplicate group, reporting each outcome
+    pub async fn resolve_duplicates(
+        &self,
+        group: &DuplicateGroup,
+        resolution: DuplicateResolution,
+    ) -> Vec<CancelReport> {
+        let mut reports = Vec::new();
+
+        for order in group.extras(resolution) {
+            reports.push(CancelReport {
+                order_id: order.order_id,
+                result: self
+                    .cancel_order(order.coin_pair.clone(), order.order_id)
+                    .await
+                    .map(|response| response.order),
+            });
+        }
+
+        reports
+    }
+}
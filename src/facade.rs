@@ -0,0 +1,143 @@
+use crate::client::{Client, Error, OrderType};
+use crate::currency::Currency;
+use crate::normalized::{symbol, NormalizedBalance, NormalizedBook, NormalizedTicker};
+use crate::v4;
+
+/// Public: One API over the v3 and v4 clients, routing each call to the v4
+/// client when it's configured and to the v3 one otherwise, so applications
+/// can migrate one account at a time.
+///
+/// With both clients configured, orders go through the same pre-trade
+/// checks whichever route they take: the v4 client shares the switchboard
+/// (which safe mode switches pairs off in) and the exposure limits of the
+/// v3 one, and the v3 endpoint allowlist is checked for the equivalent TAPI
+/// method.
+#[derive(Default)]
+pub struct MercadoBitcoin {
+    v3: Option<Client>,
+    v4: Option<(v4::Client, String)>,
+}
+
+impl MercadoBitcoin {
+    /// Public: A facade without clients, add them with `with_v3` and `with_v4`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Public: Use a v3 client for calls the v4 one can't serve
+    pub fn with_v3(mut self, client: Client) -> Self {
+        self.v3 = Some(client);
+        self.share_checks()
+    }
+
+    /// Public: Use a v4 client acting on the account `account_id`
+    pub fn with_v4(mut self, client: v4::Client, account_id: String) -> Self {
+        self.v4 = Some((client, account_id));
+        self.share_checks()
+    }
+
+    /// Give the v4 client the switchboard and exposure limits of the v3 one
+    fn share_checks(mut self) -> Self {
+        if let (Some(v3), Some((v4, account_id))) = (&self.v3, self.v4.take()) {
+            let mut v4 = v4.with_switchboard(v3.switchboard().clone());
+            if let Some(limits) = v3.exposure_limits() {
+                v4 = v4.with_exposure_limits(limits.clone());
+            }
+            self.v4 = Some((v4, account_id));
+        }
+        self
+    }
+
+    fn v3(&self) -> Result<&Client, Error> {
+        self.v3
+            .as_ref()
+            .ok_or_else(|| Error::ClientNotConfigured("v3 or v4".to_string()))
+    }
+
+    pub async fn ticker(&self, currency: &Currency) -> Result<NormalizedTicker, Error> {
+        if let Some((client, _)) = &self.v4 {
+            let ticker = client
+                .tickers(&[&symbol(currency)])
+                .await?
+                .pop()
+                .ok_or_else(|| {
+                    Error::UnexpectedResponse(format!("no {} ticker", symbol(currency)))
+                })?;
+            return Ok(NormalizedTicker::from_v4(&ticker));
+        }
+
+        let ticker = self.v3()?.ticker(currency).await?.ticker;
+        Ok(NormalizedTicker::from_v3(currency, &ticker))
+    }
+
+    pub async fn orderbook(&self, currency: &Currency) -> Result<NormalizedBook, Error> {
+        if let Some((client, _)) = &self.v4 {
+            let symbol = symbol(currency);
+            let orderbook = client.orderbook(&symbol, None).await?;
+            return Ok(NormalizedBook::from_v4(&symbol, &orderbook));
+        }
+
+        let orderbook = self.v3()?.public_orderbook(currency).await?;
+        Ok(NormalizedBook::from_v3(currency, &orderbook))
+    }
+
+    pub async fn balances(&self) -> Result<Vec<NormalizedBalance>, Error> {
+        if let Some((client, account_id)) = &self.v4 {
+            let balances = client.balances(account_id).await?;
+            return Ok(balances.iter().map(NormalizedBalance::from_v4).collect());
+        }
+
+        let balances = self.v3()?.get_account_info().await?.balance;
+        Ok(NormalizedBalance::from_v3(&balances))
+    }
+
+    /// Place a limit order buying or selling `currency` for BRL, returning
+    /// the order id
+    pub async fn place_limit_order(
+        &self,
+        currency: &Currency,
+        order_type: OrderType,
+        quantity: f64,
+        limit_price: f64,
+    ) -> Result<String, Error> {
+        if let Some((client, account_id)) = &self.v4 {
+            if let Some(v3) = &self.v3 {
+                v3.check_allowed(&order_type.place_order_name())?;
+            }
+            let side = match order_type {
+                OrderType::Buy => v4::Side::Buy,
+                OrderType::Sell => v4::Side::Sell,
+            };
+            let request = v4::OrderRequest::limit(side, quantity, limit_price);
+            let placed = client
+                .place_order(account_id, &symbol(currency), &request)
+                .await?;
+            return Ok(placed.order_id);
+        }
+
+        let coin_pair = format!("BRL{}", currency);
+        let order = self
+            .v3()?
+            .place_order(order_type, quantity, limit_price, coin_pair)
+            .await?
+            .order;
+        Ok(order.order_id.to_string())
+    }
+
+    pub async fn cancel_order(&self, currency: &Currency, order_id: &str) -> Result<(), Error> {
+        if let Some((client, account_id)) = &self.v4 {
+            client
+                .cancel_order(account_id, &symbol(currency), order_id)
+                .await?;
+            return Ok(());
+        }
+
+        let order_id = order_id
+            .parse()
+            .map_err(|_| Error::InvalidOrderId(order_id.to_string()))?;
+        self.v3()?
+            .cancel_order(format!("BRL{}", currency), order_id)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,133 @@
+//! Resumable download of the public trade history.
+
+use crate::client::{Client, Error, PublicTrade};
+use crate::currency::Currency;
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const CHECKPOINT_VERSION: u32 = 1;
+
+/// Public: Destination of downloaded trades. Each page is handed over in
+/// `tid` order before the checkpoint moves past it.
+pub trait TradeSink {
+    fn write(&mut self, trades: &[PublicTrade]) -> Result<(), Error>;
+}
+
+impl<F> TradeSink for F
+where
+    F: FnMut(&[PublicTrade]) -> Result<(), Error>,
+{
+    fn write(&mut self, trades: &[PublicTrade]) -> Result<(), Error> {
+        self(trades)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Checkpoint {
+    pub version: u32,
+    pub currency: String,
+    /// Last trade handed to the sink
+    pub last_tid: i64,
+}
+
+/// Public: Pages through `/trades?tid=` for a currency, persisting a
+/// checkpoint after every page so an interrupted download resumes where it
+/// stopped.
+pub struct HistoryDownloader<'a> {
+    client: &'a Client,
+    currency: Currency,
+    checkpoint_path: PathBuf,
+    start_tid: i64,
+    page_delay: Duration,
+}
+
+impl<'a> HistoryDownloader<'a> {
+    pub fn new(client: &'a Client, currency: Currency, checkpoint_path: impl AsRef<Path>) -> Self {
+        HistoryDownloader {
+            client,
+            currency,
+            checkpoint_path: checkpoint_path.as_ref().to_path_buf(),
+            start_tid: 0,
+            page_delay: Duration::from_secs(1),
+        }
+    }
+
+    /// Where to start when there is no checkpoint yet
+    pub fn starting_after(mut self, tid: i64) -> Self {
+        self.start_tid = tid;
+        self
+    }
+
+    /// Pause between pages to stay within the public API rate limit
+    pub fn with_page_delay(mut self, page_delay: Duration) -> Self {
+        self.page_delay = page_delay;
+        self
+    }
+
+    pub async fn checkpoint(&self) -> Result<Option<Checkpoint>, Error> {
+        let bytes = match tokio::fs::read(&self.checkpoint_path).await {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        let checkpoint: Checkpoint = serde_json::from_slice(&bytes).map_err(io::Error::from)?;
+        if checkpoint.version != CHECKPOINT_VERSION
+            || checkpoint.currency != self.currency.to_string()
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("checkpoint {:?} does not match this download", checkpoint),
+            )
+            .into());
+        }
+
+        Ok(Some(checkpoint))
+    }
+
+    /// Download until the latest trade, returning how many trades were
+    /// written to `sink`
+    pub async fn run<S: TradeSink>(&self, sink: &mut S) -> Result<u64, Error> {
+        let mut last_tid = match self.checkpoint().await? {
+            Some(checkpoint) => checkpoint.last_tid,
+            None => self.start_tid,
+        };
+        let mut written = 0;
+
+        loop {
+            let mut trades = self.client.trades_since(&self.currency, last_tid).await?;
+            trades.retain(|trade| trade.tid > last_tid);
+            if trades.is_empty() {
+                return Ok(written);
+            }
+
+            trades.sort_by_key(|trade| trade.tid);
+            sink.write(&trades)?;
+
+            written += trades.len() as u64;
+            last_tid = trades.last().unwrap().tid;
+            self.save_checkpoint(last_tid).await?;
+
+            tokio::time::sleep(self.page_delay).await;
+        }
+    }
+
+    /// Write to a temporary file first so a crash never leaves a partial
+    /// checkpoint behind
+    async fn save_checkpoint(&self, last_tid: i64) -> Result<(), Error> {
+        let checkpoint = Checkpoint {
+            version: CHECKPOINT_VERSION,
+            currency: self.currency.to_string(),
+            last_tid,
+        };
+        let bytes = serde_json::to_vec(&checkpoint).map_err(io::Error::from)?;
+
+        let tmp = self.checkpoint_path.with_extension("tmp");
+        tokio::fs::write(&tmp, bytes).await?;
+        tokio::fs::rename(&tmp, &self.checkpoint_path).await?;
+
+        Ok(())
+    }
+}
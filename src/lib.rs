@@ -1,3 +1,59 @@
+mod access;
+mod accounts;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+mod cache;
+mod calendar;
 mod client;
+#[cfg(feature = "config")]
+mod config;
+mod convert;
+mod credentials;
+mod currency;
+mod duplicates;
+mod facade;
+pub mod history;
+pub mod normalized;
+mod options;
+mod portfolio;
+mod queue;
+mod risk;
+pub mod rules;
+mod safe_mode;
+pub mod sim;
+mod snapshot;
+pub mod streams;
+mod switchboard;
+mod transport;
+pub mod v4;
+mod watchdog;
+pub mod ws;
 
-pub use client::{Client, Error};
+pub use access::{AuthenticatedClient, PublicClient};
+pub use accounts::Accounts;
+pub use cache::DaySummaryCache;
+pub use calendar::{Halt, HaltCalendar, HaltKind};
+pub use client::{
+    AccountInfoResponse, ApiStatus, AssetFees, AssetNetwork, Balance, BalancesResponse,
+    CancelReport, Client, ClientBuilder, DayBoundary, DaySummary, Environment, Error,
+    ListOrdersFilter, ListOrdersResponse, Operation, Order, OrderResponse, OrderStatus, OrderType,
+    Orderbook, OrderbookOrder, OrderbookResponse, ParamFormat, PendingOrder, Placement, PriceLevel,
+    ProxyConfig, PublicOrderbook, PublicTrade, ReloadConfig, Resolution, Response, RetryPolicy,
+    SignedRequest, Symbol, Ticker, TickerResponse, TradeType, WithdrawalEstimate, WithdrawalLimits,
+    DEFAULT_USER_AGENT, PUBLIC_API_URL, TAPI_URL, V4_API_URL,
+};
+pub use convert::{ConversionOptions, ConversionReceipt};
+pub use credentials::{Credentials, HmacSigner, Signer};
+pub use currency::Currency;
+pub use duplicates::{DuplicateGroup, DuplicateResolution};
+pub use facade::MercadoBitcoin;
+pub use options::RequestOptions;
+pub use portfolio::{Holding, Portfolio, PortfolioValuation, PositionValuation};
+pub use queue::{estimate_queue_position, QueueEstimate, RecentTrades};
+pub use risk::{BreachPolicy, ExposureLimits};
+pub use safe_mode::{SafeModeEvent, SafeModePolicy, SafeModeTrigger};
+pub use snapshot::AccountSnapshot;
+pub use switchboard::Switchboard;
+pub use transport::{HttpMethod, HttpRequest, HttpResponse, HttpTransport, Middleware};
+pub use v4::Candle;
+pub use watchdog::DeadMansSwitch;
@@ -0,0 +1,241 @@
+//! Domain types independent of the API generation they were fetched from,
+//! so code written against them survives the move from v3 to v4.
+
+use crate::client::{
+    BalancesResponse, PriceLevel, PublicOrderbook, PublicTrade, Ticker, TradeType,
+};
+use crate::currency::Currency;
+use crate::v4;
+use crate::ws;
+use chrono::{DateTime, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+
+/// Symbols are written the v4 way, e.g. `BTC-BRL`
+pub(crate) fn symbol(currency: &Currency) -> String {
+    format!("{}-BRL", currency)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NormalizedTicker {
+    pub symbol: String,
+    pub last: f64,
+    pub bid: f64,
+    pub ask: f64,
+    pub high: f64,
+    pub low: f64,
+    pub volume: f64,
+    pub time: DateTime<Utc>,
+}
+
+impl NormalizedTicker {
+    pub fn from_v3(currency: &Currency, ticker: &Ticker) -> Self {
+        NormalizedTicker {
+            symbol: symbol(currency),
+            last: ticker.last as f64,
+            bid: ticker.buy as f64,
+            ask: ticker.sell as f64,
+            high: ticker.high as f64,
+            low: ticker.low as f64,
+            volume: ticker.vol as f64,
+            time: ticker.date,
+        }
+    }
+
+    pub fn from_v4(ticker: &v4::Ticker) -> Self {
+        NormalizedTicker {
+            symbol: ticker.pair.clone(),
+            last: ticker.last,
+            bid: ticker.buy,
+            ask: ticker.sell,
+            high: ticker.high,
+            low: ticker.low,
+            volume: ticker.vol,
+            time: ticker.date,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NormalizedTrade {
+    pub symbol: String,
+    pub id: i64,
+    pub side: TradeType,
+    pub price: f64,
+    pub quantity: f64,
+    pub time: DateTime<Utc>,
+}
+
+impl NormalizedTrade {
+    pub fn from_v3(currency: &Currency, trade: &PublicTrade) -> Self {
+        NormalizedTrade {
+            symbol: symbol(currency),
+            id: trade.tid,
+            side: trade.trade_type,
+            price: trade.price,
+            quantity: trade.amount,
+            time: trade.date,
+        }
+    }
+
+    pub fn from_v4(symbol: &str, trade: &v4::Trade) -> Self {
+        NormalizedTrade {
+            symbol: symbol.to_string(),
+            id: trade.tid,
+            side: trade.trade_type,
+            price: to_f64(trade.price),
+            quantity: to_f64(trade.amount),
+            time: trade.date,
+        }
+    }
+
+    /// A trade pushed by the websocket feed for `coin_pair`, e.g. `BRLBTC`
+    pub fn from_ws(coin_pair: &str, trade: &ws::Trade) -> Self {
+        NormalizedTrade {
+            symbol: ws_symbol(coin_pair),
+            id: trade.tid,
+            side: trade.trade_type,
+            price: to_f64(trade.price),
+            quantity: to_f64(trade.amount),
+            time: trade.date,
+        }
+    }
+}
+
+/// The symbol of a websocket coin pair, e.g. `BTC-BRL` for `BRLBTC`
+fn ws_symbol(coin_pair: &str) -> String {
+    let coin_pair = coin_pair.to_uppercase();
+    let asset = coin_pair.strip_prefix("BRL").unwrap_or(&coin_pair);
+    format!("{}-BRL", asset)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NormalizedBook {
+    pub symbol: String,
+    /// Best (highest) bid first
+    pub bids: Vec<PriceLevel>,
+    /// Best (lowest) ask first
+    pub asks: Vec<PriceLevel>,
+}
+
+impl NormalizedBook {
+    pub fn from_v3(currency: &Currency, orderbook: &PublicOrderbook) -> Self {
+        NormalizedBook {
+            symbol: symbol(currency),
+            bids: orderbook.bids.clone(),
+            asks: orderbook.asks.clone(),
+        }
+    }
+
+    pub fn from_v4(symbol: &str, orderbook: &v4::Orderbook) -> Self {
+        let levels = |levels: &[v4::PriceLevel]| {
+            levels
+                .iter()
+                .map(|level| PriceLevel {
+                    price: to_f64(level.price),
+                    quantity: to_f64(level.quantity),
+                })
+                .collect()
+        };
+
+        NormalizedBook {
+            symbol: symbol.to_string(),
+            bids: levels(&orderbook.bids),
+            asks: levels(&orderbook.asks),
+        }
+    }
+
+    pub fn best_bid(&self) -> Option<&PriceLevel> {
+        self.bids.first()
+    }
+
+    pub fn best_ask(&self) -> Option<&PriceLevel> {
+        self.asks.first()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NormalizedBalance {
+    pub currency: Currency,
+    pub available: f64,
+    pub total: f64,
+}
+
+impl NormalizedBalance {
+    pub fn from_v3(balances: &BalancesResponse) -> Vec<Self> {
+        let currencies = [
+            Currency::Brl,
+            Currency::Btc,
+            Currency::Eth,
+            Currency::Ltc,
+            Currency::Bch,
+            Currency::Xrp,
+            Currency::Usdc,
+            Currency::Paxg,
+        ];
+
+        currencies
+            .iter()
+            .filter_map(|currency| {
+                let balance = balances.get(currency)?;
+                Some(NormalizedBalance {
+                    currency: currency.clone(),
+                    available: balance.available,
+                    total: balance.total,
+                })
+            })
+            .collect()
+    }
+
+    pub fn from_v4(balance: &v4::Balance) -> Self {
+        NormalizedBalance {
+            currency: balance.symbol.parse().unwrap(),
+            available: balance.available,
+            total: balance.total,
+        }
+    }
+}
+
+fn to_f64(value: Decimal) -> f64 {
+    value.to_f64().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::str::FromStr;
+
+    #[test]
+    fn trades_normalize_the_same_from_every_source() {
+        let date = Utc.timestamp_opt(1_600_000_000, 0).unwrap();
+        let decimal = |value| Decimal::from_str(value).unwrap();
+
+        let v3 = PublicTrade {
+            tid: 42,
+            date,
+            trade_type: TradeType::Buy,
+            price: 100.5,
+            amount: 0.25,
+        };
+        let v4 = v4::Trade {
+            tid: 42,
+            date,
+            trade_type: TradeType::Buy,
+            price: decimal("100.5"),
+            amount: decimal("0.25"),
+        };
+        let ws = ws::Trade {
+            tid: 42,
+            date,
+            trade_type: TradeType::Buy,
+            price: decimal("100.5"),
+            amount: decimal("0.25"),
+        };
+
+        let expected = NormalizedTrade::from_v3(&Currency::Btc, &v3);
+        assert_eq!(expected.symbol, "BTC-BRL");
+        assert_eq!(NormalizedTrade::from_v4("BTC-BRL", &v4), expected);
+        assert_eq!(NormalizedTrade::from_ws("BRLBTC", &ws), expected);
+    }
+}
@@ -0,0 +1,43 @@
+use crate::client::Client;
+use std::future::Future;
+use std::time::Duration;
+
+tokio::task_local! {
+    static REQUEST_OPTIONS: RequestOptions;
+}
+
+/// Public: Overrides for the requests made within `Client::using_options`,
+/// e.g. a tight timeout for order placement and a generous one for history
+/// backfills
+#[derive(Debug, Clone, Default)]
+pub struct RequestOptions {
+    /// Replaces the client's timeout, see `ClientBuilder::with_timeout`
+    pub timeout: Option<Duration>,
+}
+
+impl RequestOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+}
+
+/// Options set with `Client::using_options` for the current task, if any
+pub(crate) fn request_options() -> RequestOptions {
+    REQUEST_OPTIONS
+        .try_with(RequestOptions::clone)
+        .unwrap_or_default()
+}
+
+impl Client {
+    /// Public: Run `f` with every request it makes, v3 or v4, sent with
+    /// `options`, e.g.
+    /// `Client::using_options(options, client.place_buy_order(..)).await`
+    pub async fn using_options<F: Future>(options: RequestOptions, f: F) -> F::Output {
+        REQUEST_OPTIONS.scope(options, f).await
+    }
+}
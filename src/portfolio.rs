@@ -0,0 +1,94 @@
+use crate::client::{BalancesResponse, Client, Error};
+use crate::currency::Currency;
+use chrono::{DateTime, Utc};
+
+/// A quantity of a single coin held by the user
+#[derive(Debug, Clone)]
+pub struct Holding {
+    pub currency: Currency,
+    pub quantity: f64,
+}
+
+/// Public: A set of holdings to be valued with public market data.
+///
+/// It can be declared by hand (watch-only, no API key required) or built
+/// from the balances of an authenticated account.
+#[derive(Debug, Clone, Default)]
+pub struct Portfolio {
+    pub holdings: Vec<Holding>,
+}
+
+impl Portfolio {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Public: Declare a manual holding, e.g. coins kept in cold storage
+    pub fn hold(mut self, currency: Currency, quantity: f64) -> Self {
+        self.holdings.push(Holding { currency, quantity });
+        self
+    }
+}
+
+impl From<&BalancesResponse> for Portfolio {
+    fn from(balances: &BalancesResponse) -> Self {
+        Portfolio::new()
+            .hold(Currency::Brl, balances.brl.total)
+            .hold(Currency::Btc, balances.btc.total)
+            .hold(Currency::Bch, balances.bch.total)
+            .hold(Currency::Eth, balances.eth.total)
+            .hold(Currency::Ltc, balances.ltc.total)
+            .hold(Currency::Xrp, balances.xrp.total)
+            .hold(Currency::Usdc, balances.usdc.total)
+            .hold(Currency::Paxg, balances.paxg.total)
+    }
+}
+
+#[derive(Debug)]
+pub struct PositionValuation {
+    pub currency: Currency,
+    pub quantity: f64,
+    /// Last traded price in BRL
+    pub price: f64,
+    /// Position value in BRL
+    pub value: f64,
+}
+
+#[derive(Debug)]
+pub struct PortfolioValuation {
+    pub positions: Vec<PositionValuation>,
+    /// Net asset value in BRL
+    pub nav: f64,
+    pub date: DateTime<Utc>,
+}
+
+impl Client {
+    /// Value a portfolio in BRL using the last price of each coin's public ticker.
+    /// Only the public API is used, so this works on a client without credentials.
+    pub async fn value_portfolio(
+        &self,
+        portfolio: &Portfolio,
+    ) -> Result<PortfolioValuation, Error> {
+        let mut positions = Vec::with_capacity(portfolio.holdings.len());
+
+        for holding in &portfolio.holdings {
+            let price = match &holding.currency {
+                Currency::Brl => 1.0,
+                currency => self.ticker(currency).await?.ticker.last as f64,
+            };
+
+            positions.push(PositionValuation {
+                currency: holding.currency.clone(),
+                quantity: holding.quantity,
+                price,
+                value: holding.quantity * price,
+            });
+        }
+
+        Ok(PortfolioValuation {
+            nav: positions.iter().map(|p| p.value).sum(),
+            positions,
+            date: Utc::now(),
+        })
+    }
+}
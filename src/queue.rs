@@ -0,0 +1,210 @@
+use crate::client::{Client, Error, Order, OrderType, TradeType};
+use crate::ws::{BookSide, Event, LiveOrderbook, Trade};
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+use std::collections::VecDeque;
+
+/// Public: Where a resting limit order stands in its price level and how
+/// long it is expected to wait for a fill
+#[derive(Debug, Clone)]
+pub struct QueueEstimate {
+    /// Quantity at the order price that fills first, at most. The book
+    /// doesn't tell where in the level the order is, so this assumes it's
+    /// last.
+    pub quantity_ahead: Decimal,
+    /// Quantity of the order still to fill
+    pub remaining: Decimal,
+    /// Quantity traded against the order's side at its price or better, per
+    /// second, over the lookback window
+    pub fill_rate: Decimal,
+    /// `None` when nothing traded at the order price during the window
+    pub time_to_fill: Option<Duration>,
+}
+
+/// Public: The trades of a pair over a sliding window, fed from the
+/// websocket trade stream
+#[derive(Debug, Clone)]
+pub struct RecentTrades {
+    coin_pair: String,
+    lookback: Duration,
+    trades: VecDeque<Trade>,
+}
+
+impl RecentTrades {
+    /// Public: Keep the trades of `coin_pair`, e.g. `BRLBTC`, of the last
+    /// `lookback`
+    pub fn new(coin_pair: &str, lookback: Duration) -> Self {
+        RecentTrades {
+            coin_pair: coin_pair.to_uppercase(),
+            lookback,
+            trades: VecDeque::new(),
+        }
+    }
+
+    pub fn coin_pair(&self) -> &str {
+        &self.coin_pair
+    }
+
+    pub fn lookback(&self) -> Duration {
+        self.lookback
+    }
+
+    /// Add a trade, dropping the ones that fell out of the window
+    pub fn push(&mut self, trade: Trade) {
+        let since = trade.date - self.lookback;
+        self.trades.push_back(trade);
+        while self.trades.front().is_some_and(|trade| trade.date < since) {
+            self.trades.pop_front();
+        }
+    }
+
+    /// Add the trade of a trade event of this pair, ignoring every other
+    /// event. Returns whether a trade was added.
+    pub fn apply_event(&mut self, event: &Event) -> bool {
+        match event {
+            Event::Trade { coin_pair, trade } if *coin_pair == self.coin_pair => {
+                self.push(trade.clone());
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Trades of the window ending at `now`
+    pub fn trades(&self, now: DateTime<Utc>) -> impl Iterator<Item = &Trade> {
+        let since = now - self.lookback;
+        self.trades.iter().filter(move |trade| trade.date >= since)
+    }
+}
+
+/// Estimate the queue position of `order` from the live book and the trades
+/// of the window ending at `now`
+pub fn estimate_queue_position(
+    order: &Order,
+    book: &LiveOrderbook,
+    trades: &RecentTrades,
+    now: DateTime<Utc>,
+) -> QueueEstimate {
+    let decimal = |value: f64| Decimal::from_f64(value).unwrap_or_default();
+    let limit_price = decimal(order.limit_price);
+    let remaining = decimal(order.quantity) - decimal(order.executed_quantity);
+
+    let (side, taker_side) = match order.order_type {
+        OrderType::Buy => (BookSide::Bid, TradeType::Sell),
+        OrderType::Sell => (BookSide::Ask, TradeType::Buy),
+    };
+    let at_or_better = |price: Decimal| match order.order_type {
+        OrderType::Buy => price <= limit_price,
+        OrderType::Sell => price >= limit_price,
+    };
+
+    let quantity_ahead = (book.quantity_at(side, limit_price) - remaining).max(Decimal::ZERO);
+
+    let traded: Decimal = trades
+        .trades(now)
+        .filter(|trade| trade.trade_type == taker_side && at_or_better(trade.price))
+        .map(|trade| trade.amount)
+        .sum();
+    let seconds = Decimal::new(trades.lookback().num_milliseconds(), 3);
+    let fill_rate = if seconds > Decimal::ZERO {
+        traded / seconds
+    } else {
+        Decimal::ZERO
+    };
+
+    let time_to_fill = if fill_rate > Decimal::ZERO {
+        let milliseconds = (quantity_ahead + remaining) / fill_rate * Decimal::from(1000);
+        milliseconds.to_i64().map(Duration::milliseconds)
+    } else {
+        None
+    };
+
+    QueueEstimate {
+        quantity_ahead,
+        remaining,
+        fill_rate,
+        time_to_fill,
+    }
+}
+
+impl Client {
+    /// Estimate the queue position and time to fill of a resting limit order
+    /// in the pair of `book`, kept live from the websocket feed, and the
+    /// `trades` streamed for it. A book that `needs_snapshot` may be outdated
+    /// and so is the estimate.
+    pub async fn queue_position(
+        &self,
+        order_id: i64,
+        book: &LiveOrderbook,
+        trades: &RecentTrades,
+    ) -> Result<QueueEstimate, Error> {
+        let order = self
+            .get_order(book.coin_pair().to_string(), order_id)
+            .await?
+            .order;
+
+        Ok(estimate_queue_position(&order, book, trades, Utc::now()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::OrderStatus;
+    use crate::ws::{Orderbook, PriceLevel};
+    use std::str::FromStr;
+
+    fn decimal(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    #[test]
+    fn levels_are_matched_exactly() {
+        let now = Utc::now();
+        let level = |price: &str, quantity: &str| PriceLevel {
+            price: decimal(price),
+            quantity: decimal(quantity),
+        };
+        let mut book = LiveOrderbook::new("BRLBTC");
+        book.apply_snapshot(&Orderbook {
+            timestamp: 1,
+            bids: vec![level("100.1", "3"), level("100.09999999", "7")],
+            asks: vec![level("101", "1")],
+        });
+
+        let mut trades = RecentTrades::new("BRLBTC", Duration::seconds(10));
+        let trade = |tid: i64, date: DateTime<Utc>, price: &str| Trade {
+            tid,
+            date,
+            trade_type: TradeType::Sell,
+            price: decimal(price),
+            amount: decimal("0.5"),
+        };
+        trades.push(trade(1, now - Duration::seconds(60), "100"));
+        trades.push(trade(2, now - Duration::seconds(5), "100.1"));
+        trades.push(trade(3, now - Duration::seconds(1), "100"));
+
+        let order = Order {
+            order_id: 1,
+            coin_pair: "BRLBTC".to_string(),
+            order_type: OrderType::Buy,
+            status: OrderStatus::Open,
+            has_fills: true,
+            quantity: 1.5,
+            limit_price: 100.1,
+            executed_quantity: 0.5,
+            executed_price_avg: 100.1,
+            fee: 0.0,
+            created_timestamp: now,
+            updated_timestamp: now,
+            operations: vec![],
+        };
+
+        let estimate = estimate_queue_position(&order, &book, &trades, now);
+        assert_eq!(estimate.remaining, decimal("1"));
+        assert_eq!(estimate.quantity_ahead, decimal("2"));
+        assert_eq!(estimate.fill_rate, decimal("0.1"));
+        assert_eq!(estimate.time_to_fill, Some(Duration::seconds(30)));
+    }
+}
@@ -0,0 +1,311 @@
+use crate::client::{ApiStatus, Client, Error, ListOrdersFilter, OrderStatus, OrderType};
+use crate::currency::Currency;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use tokio::sync::watch;
+
+/// Public: What to do with a buy order that would take an asset over its
+/// limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreachPolicy {
+    /// Fail with `Error::ExposureLimitExceeded`
+    Reject,
+    /// Place it for the quantity left under the limit, rejecting it only
+    /// when there's none
+    Shrink,
+}
+
+#[derive(Debug, Default)]
+struct Limits {
+    max_quantity: HashMap<Currency, f64>,
+    /// Holdings plus the unfilled quantity of open buy orders
+    exposure: HashMap<Currency, f64>,
+}
+
+impl Limits {
+    fn utilization(&self) -> HashMap<Currency, f64> {
+        self.max_quantity
+            .iter()
+            .map(|(asset, max)| {
+                let exposure = self.exposure.get(asset).copied().unwrap_or(0.0);
+                // A zero limit leaves no room at all, so it is always in use
+                let utilization = if *max > 0.0 { exposure / max } else { 1.0 };
+                (asset.clone(), utilization)
+            })
+            .collect()
+    }
+}
+
+/// Public: Limits on how much of each asset the account may hold, counting
+/// open buy orders as if they were filled. Given to a client with
+/// `Client::with_exposure_limits` (or `v4::Client::with_exposure_limits`),
+/// they are enforced on every buy it places, limit or market. Clones share
+/// the same limits and exposure.
+#[derive(Debug, Clone)]
+pub struct ExposureLimits {
+    policy: BreachPolicy,
+    limits: Arc<RwLock<Limits>>,
+    utilization: Arc<watch::Sender<HashMap<Currency, f64>>>,
+}
+
+impl ExposureLimits {
+    pub fn new(policy: BreachPolicy) -> Self {
+        ExposureLimits {
+            policy,
+            limits: Default::default(),
+            utilization: Arc::new(watch::channel(HashMap::new()).0),
+        }
+    }
+
+    /// Hold at most `max_quantity` units of `asset`
+    pub fn set_limit(&self, asset: Currency, max_quantity: f64) {
+        let mut limits = self.limits.write().unwrap();
+        limits.max_quantity.insert(asset, max_quantity);
+        self.publish(&limits);
+    }
+
+    /// Current exposure to `asset`, as of the last `Client::refresh_exposure`
+    /// plus the buys placed since
+    pub fn exposure(&self, asset: &Currency) -> f64 {
+        let limits = self.limits.read().unwrap();
+        limits.exposure.get(asset).copied().unwrap_or(0.0)
+    }
+
+    /// Fraction of the limit of each asset in use (1.0 = at the limit)
+    pub fn utilization(&self) -> HashMap<Currency, f64> {
+        self.limits.read().unwrap().utilization()
+    }
+
+    /// Receive the utilization of every asset each time a limit or the
+    /// exposure changes, e.g. to export it as a metric or alert near a limit
+    pub fn subscribe(&self) -> watch::Receiver<HashMap<Currency, f64>> {
+        self.utilization.subscribe()
+    }
+
+    fn publish(&self, limits: &Limits) {
+        self.utilization.send_replace(limits.utilization());
+    }
+
+    fn set_exposure(&self, asset: Currency, exposure: f64) {
+        let mut limits = self.limits.write().unwrap();
+        limits.exposure.insert(asset, exposure);
+        self.publish(&limits);
+    }
+
+    /// Quantity of a buy that may be placed, recording it as exposure
+    pub(crate) fn admit_buy(&self, asset: &Currency, quantity: f64) -> Result<f64, Error> {
+        let mut limits = self.limits.write().unwrap();
+        let max = match limits.max_quantity.get(asset) {
+            Some(max) => *max,
+            None => return Ok(quantity),
+        };
+        let exposure = limits.exposure.entry(asset.clone()).or_insert(0.0);
+        let room = (max - *exposure).max(0.0);
+
+        let admitted = match self.policy {
+            BreachPolicy::Reject if quantity > room => 0.0,
+            _ => quantity.min(room),
+        };
+        if admitted <= 0.0 {
+            return Err(Error::ExposureLimitExceeded(format!(
+                "{} {} held or ordered, limit is {}",
+                exposure, asset, max
+            )));
+        }
+
+        *exposure += admitted;
+        self.publish(&limits);
+        Ok(admitted)
+    }
+
+    /// Give back the exposure of an admitted buy that wasn't placed
+    pub(crate) fn release_buy(&self, asset: &Currency, quantity: f64) {
+        let mut limits = self.limits.write().unwrap();
+        if let Some(exposure) = limits.exposure.get_mut(asset) {
+            *exposure -= quantity;
+            self.publish(&limits);
+        }
+    }
+}
+
+/// Whether a failed placement certainly didn't create an order: it was
+/// refused before being sent, never reached the exchange, or was rejected by
+/// it. Timeouts, server errors and unreadable responses leave it unknown.
+pub(crate) fn is_rejection(error: &Error) -> bool {
+    match error {
+        Error::ApiError(status) => {
+            *status != ApiStatus::OrderProcessing && *status != ApiStatus::InternalError
+        }
+        Error::V4ApiError(error) => (400..500).contains(&error.status),
+        Error::RequestError(error) => error.is_connect() || error.is_builder(),
+        Error::ClientNotConfigured(_)
+        | Error::SignerError(_)
+        | Error::ExposureLimitExceeded(_)
+        | Error::InvalidOrderParam(_)
+        | Error::TradingDisabled(_)
+        | Error::EndpointNotAllowed(_) => true,
+        _ => false,
+    }
+}
+
+/// The asset bought and sold in a coin pair, e.g. BTC for `BRLBTC`
+fn asset_of(coin_pair: &str) -> Currency {
+    coin_pair
+        .to_uppercase()
+        .trim_start_matches("BRL")
+        .parse()
+        .unwrap()
+}
+
+impl Client {
+    /// Quantity of an order the exposure limits let through. Sells always
+    /// pass, they can only reduce exposure.
+    pub(crate) fn admit_order(
+        &self,
+        order_type: OrderType,
+        coin_pair: &str,
+        quantity: f64,
+    ) -> Result<f64, Error> {
+        match (order_type, self.exposure_limits()) {
+            (OrderType::Buy, Some(limits)) => limits.admit_buy(&asset_of(coin_pair), quantity),
+            _ => Ok(quantity),
+        }
+    }
+
+    /// Cost of a market buy the exposure limits let through, with the
+    /// quantity it's expected to buy. The cost is converted at the best ask,
+    /// so the order can't buy more than admitted.
+    pub(crate) async fn admit_market_buy(
+        &self,
+        coin_pair: &str,
+        cost: f64,
+    ) -> Result<(f64, f64), Error> {
+        let limits = match self.exposure_limits() {
+            Some(limits) => limits,
+            None => return Ok((cost, 0.0)),
+        };
+        let asset = asset_of(coin_pair);
+        let ask = f64::from(self.ticker(&asset).await?.ticker.sell);
+        if ask <= 0.0 {
+            return Err(Error::ExposureLimitExceeded(format!(
+                "no {} ask to price a market buy at",
+                asset
+            )));
+        }
+
+        let quantity = limits.admit_buy(&asset, cost / ask)?;
+        Ok((quantity * ask, quantity))
+    }
+
+    /// Quantity a `place_*` TAPI method called through `call_tapi` adds to
+    /// the exposure, after checking it against the limits. The quantity of a
+    /// market buy is priced at the best ask; one the limits would shrink is
+    /// rejected, as its params can't be changed.
+    pub(crate) async fn admit_tapi_call(
+        &self,
+        method: &str,
+        params: &[(&str, &str)],
+    ) -> Result<f64, Error> {
+        if self.exposure_limits().is_none() {
+            return Ok(0.0);
+        }
+        let param = |name: &str| {
+            params
+                .iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| *value)
+                .ok_or_else(|| Error::ExposureLimitExceeded(format!("{} without {}", method, name)))
+        };
+        let number = |name: &str| {
+            param(name)?.parse::<f64>().map_err(|_| {
+                Error::ExposureLimitExceeded(format!("{} with an invalid {}", method, name))
+            })
+        };
+
+        let coin_pair = param("coin_pair")?;
+        let (admitted, shrunk) = match method {
+            "place_buy_order" => {
+                let quantity = number("quantity")?;
+                let admitted = self.admit_order(OrderType::Buy, coin_pair, quantity)?;
+                (admitted, admitted < quantity)
+            }
+            "place_market_buy_order" => {
+                let cost = number("cost")?;
+                let (admitted_cost, admitted) = self.admit_market_buy(coin_pair, cost).await?;
+                (admitted, admitted_cost < cost * (1.0 - 1e-9))
+            }
+            _ => return Ok(0.0),
+        };
+
+        if shrunk {
+            self.release_order(OrderType::Buy, coin_pair, admitted);
+            return Err(Error::ExposureLimitExceeded(format!(
+                "{} in {} over the limit",
+                method, coin_pair
+            )));
+        }
+        Ok(admitted)
+    }
+
+    /// Give back the exposure of an admitted order that certainly wasn't
+    /// placed, see `is_rejection`
+    pub(crate) fn release_order(&self, order_type: OrderType, coin_pair: &str, quantity: f64) {
+        if let (OrderType::Buy, Some(limits)) = (order_type, self.exposure_limits()) {
+            limits.release_buy(&asset_of(coin_pair), quantity);
+        }
+    }
+
+    /// Recompute the exposure to each asset from the account balances and
+    /// the open buy orders of its `BRL<asset>` pair
+    pub async fn refresh_exposure(&self, assets: &[Currency]) -> Result<(), Error> {
+        let limits = match self.exposure_limits() {
+            Some(limits) => limits,
+            None => return Ok(()),
+        };
+
+        let balances = self.get_account_info().await?.balance;
+        let filter = ListOrdersFilter {
+            order_type: Some(OrderType::Buy),
+            status_list: vec![OrderStatus::Open],
+            ..Default::default()
+        };
+
+        for asset in assets {
+            let held = balances.get(asset).map(|b| b.total).unwrap_or(0.0);
+            let ordered: f64 = self
+                .list_orders(format!("BRL{}", asset), &filter)
+                .await?
+                .orders
+                .iter()
+                .map(|order| order.quantity - order.executed_quantity)
+                .sum();
+
+            limits.set_exposure(asset.clone(), held + ordered);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_limits_are_fully_utilized() {
+        let limits = ExposureLimits::new(BreachPolicy::Reject);
+        let updates = limits.subscribe();
+        limits.set_limit(Currency::Btc, 0.0);
+
+        assert_eq!(limits.utilization()[&Currency::Btc], 1.0);
+        assert_eq!(updates.borrow()[&Currency::Btc], 1.0);
+        assert!(limits.admit_buy(&Currency::Btc, 0.1).is_err());
+    }
+
+    #[test]
+    fn internal_errors_keep_the_exposure_held() {
+        assert!(!is_rejection(&Error::ApiError(ApiStatus::InternalError)));
+        assert!(!is_rejection(&Error::ApiError(ApiStatus::OrderProcessing)));
+        assert!(is_rejection(&Error::ApiError(ApiStatus::InvalidCoinPair)));
+    }
+}
@@ -0,0 +1,173 @@
+//! A small rules engine for conditional orders, configurable through serde.
+//!
+//! A rule like "if the spread is above 80 bps and there are more than 1000
+//! BRL available, buy 0.001 at 20 bps under the mid price" reads as:
+//!
+//! ```json
+//! {
+//!   "name": "wide-spread-buy",
+//!   "when": [
+//!     { "metric": { "type": "spread_bps" }, "op": ">", "value": 80 },
+//!     { "metric": { "type": "balance", "currency": "BRL" }, "op": ">", "value": 1000 }
+//!   ],
+//!   "then": { "type": "place_buy", "offset_bps": -20, "quantity": 0.001 }
+//! }
+//! ```
+
+use crate::client::{Client, Error, Order};
+use crate::currency::Currency;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Metric {
+    /// Difference between the best ask and bid, in basis points of the mid price
+    SpreadBps,
+    Mid,
+    Last,
+    /// Available balance of a currency
+    Balance {
+        currency: Currency,
+    },
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    #[serde(rename = ">")]
+    Greater,
+    #[serde(rename = ">=")]
+    GreaterOrEqual,
+    #[serde(rename = "<")]
+    Less,
+    #[serde(rename = "<=")]
+    LessOrEqual,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Condition {
+    pub metric: Metric,
+    pub op: Comparison,
+    pub value: f64,
+}
+
+/// Orders are priced relative to the mid price, `offset_bps` may be negative
+#[derive(Deserialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Action {
+    PlaceBuy { offset_bps: f64, quantity: f64 },
+    PlaceSell { offset_bps: f64, quantity: f64 },
+}
+
+/// Public: Execute `then` when every condition in `when` holds
+#[derive(Deserialize, Debug, Clone)]
+pub struct Rule {
+    pub name: String,
+    pub when: Vec<Condition>,
+    pub then: Action,
+}
+
+/// Market and account data rules are evaluated against
+#[derive(Debug, Clone)]
+pub struct RuleContext {
+    pub bid: f64,
+    pub ask: f64,
+    pub last: f64,
+    pub balances: HashMap<Currency, f64>,
+}
+
+impl RuleContext {
+    pub fn mid(&self) -> f64 {
+        (self.bid + self.ask) / 2.0
+    }
+
+    fn metric(&self, metric: &Metric) -> Option<f64> {
+        match metric {
+            Metric::SpreadBps => Some((self.ask - self.bid) / self.mid() * 10_000.0),
+            Metric::Mid => Some(self.mid()),
+            Metric::Last => Some(self.last),
+            Metric::Balance { currency } => self.balances.get(currency).copied(),
+        }
+    }
+}
+
+impl Rule {
+    /// Whether every condition holds. A condition on a metric missing from
+    /// the context never holds.
+    pub fn matches(&self, context: &RuleContext) -> bool {
+        self.when.iter().all(|condition| {
+            let value = match context.metric(&condition.metric) {
+                Some(value) => value,
+                None => return false,
+            };
+
+            match condition.op {
+                Comparison::Greater => value > condition.value,
+                Comparison::GreaterOrEqual => value >= condition.value,
+                Comparison::Less => value < condition.value,
+                Comparison::LessOrEqual => value <= condition.value,
+            }
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct RuleOutcome {
+    pub rule: String,
+    pub order: Result<Order, Error>,
+}
+
+impl Client {
+    /// Build a `RuleContext` for `currency` from its ticker and the account
+    /// balances, and execute the action of every rule that matches
+    pub async fn run_rules(
+        &self,
+        currency: &Currency,
+        rules: &[Rule],
+    ) -> Result<Vec<RuleOutcome>, Error> {
+        let ticker = self.ticker(currency).await?.ticker;
+        let account = self.get_account_info().await?;
+
+        let balances = [Currency::Brl, currency.clone()]
+            .iter()
+            .filter_map(|c| Some((c.clone(), account.balance.get(c)?.available)))
+            .collect();
+        let context = RuleContext {
+            bid: ticker.buy as f64,
+            ask: ticker.sell as f64,
+            last: ticker.last as f64,
+            balances,
+        };
+
+        let coin_pair = format!("BRL{}", currency);
+        let mut outcomes = Vec::new();
+
+        for rule in rules.iter().filter(|rule| rule.matches(&context)) {
+            let order = match rule.then {
+                Action::PlaceBuy {
+                    offset_bps,
+                    quantity,
+                } => {
+                    let price = context.mid() * (1.0 + offset_bps / 10_000.0);
+                    self.place_buy_order(quantity, price, coin_pair.clone())
+                        .await
+                }
+                Action::PlaceSell {
+                    offset_bps,
+                    quantity,
+                } => {
+                    let price = context.mid() * (1.0 + offset_bps / 10_000.0);
+                    self.place_sell_order(quantity, price, coin_pair.clone())
+                        .await
+                }
+            };
+
+            outcomes.push(RuleOutcome {
+                rule: rule.name.clone(),
+                order: order.map(|response| response.order),
+            });
+        }
+
+        Ok(outcomes)
+    }
+}
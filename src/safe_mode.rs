@@ -0,0 +1,160 @@
+use crate::client::{ApiStatus, CancelReport, Client, Error};
+use std::time::Duration;
+
+/// Public: How `Client::run_safe_mode` behaves while the exchange is down
+#[derive(Debug, Clone)]
+pub struct SafeModePolicy {
+    /// Cancel the open orders of every guarded pair on entering safe mode
+    pub cancel_open_orders: bool,
+    /// Time between recovery probes
+    pub probe_interval: Duration,
+    /// Consecutive successful probes required before resuming trading
+    pub probes_to_resume: u32,
+    /// Re-enable the guarded pairs once the exchange recovers. When `false`
+    /// they stay disabled in the switchboard for an operator to enable.
+    pub resume_trading: bool,
+}
+
+impl Default for SafeModePolicy {
+    fn default() -> Self {
+        SafeModePolicy {
+            cancel_open_orders: true,
+            probe_interval: Duration::from_secs(30),
+            probes_to_resume: 3,
+            resume_trading: true,
+        }
+    }
+}
+
+/// Public: Progress reported by `Client::run_safe_mode`, meant to be
+/// forwarded to whatever notifies the operators
+#[derive(Debug)]
+pub enum SafeModeEvent {
+    Entered {
+        coin_pairs: Vec<String>,
+    },
+    OrdersCancelled {
+        coin_pair: String,
+        reports: Vec<CancelReport>,
+    },
+    CancelFailed {
+        coin_pair: String,
+        error: Error,
+    },
+    ProbeFailed {
+        error: Error,
+    },
+    ProbeSucceeded {
+        consecutive: u32,
+    },
+    Resumed {
+        coin_pairs: Vec<String>,
+    },
+}
+
+/// Public: Decides when to enter safe mode from the results of API calls.
+///
+/// Trips after `max_failures` consecutive failed calls, or immediately when
+/// the exchange reports that trading is halted.
+#[derive(Debug, Clone)]
+pub struct SafeModeTrigger {
+    max_failures: u32,
+    failures: u32,
+}
+
+impl SafeModeTrigger {
+    pub fn new(max_failures: u32) -> Self {
+        SafeModeTrigger {
+            max_failures,
+            failures: 0,
+        }
+    }
+
+    /// Record the result of an API call, returning whether safe mode should
+    /// be entered
+    pub fn record<T>(&mut self, result: &Result<T, Error>) -> bool {
+        match result {
+            Ok(_) => {
+                self.failures = 0;
+                false
+            }
+            Err(Error::ApiError(ApiStatus::TradingHalted)) => true,
+            Err(_) => {
+                self.failures += 1;
+                self.failures >= self.max_failures
+            }
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.failures = 0;
+    }
+}
+
+impl Client {
+    /// Put `coin_pairs` in safe mode and wait for the exchange to recover.
+    ///
+    /// The pairs are disabled in the client switchboard, so every clone
+    /// sharing it stops placing orders, and their open orders are cancelled
+    /// if the policy asks for it. The trade API is then probed with
+    /// `get_account_info` every `policy.probe_interval` until it answers
+    /// `policy.probes_to_resume` times in a row, when the pairs are enabled
+    /// again (unless `policy.resume_trading` is unset). Every step is
+    /// reported to `notify`.
+    pub async fn run_safe_mode<F>(
+        &self,
+        coin_pairs: &[&str],
+        policy: &SafeModePolicy,
+        mut notify: F,
+    ) where
+        F: FnMut(SafeModeEvent),
+    {
+        for coin_pair in coin_pairs {
+            self.switchboard().disable(coin_pair);
+        }
+        notify(SafeModeEvent::Entered {
+            coin_pairs: coin_pairs.iter().map(|p| p.to_string()).collect(),
+        });
+
+        if policy.cancel_open_orders {
+            for coin_pair in coin_pairs {
+                let coin_pair = coin_pair.to_string();
+                match self.cancel_all_open_orders(coin_pair.clone()).await {
+                    Ok(reports) => notify(SafeModeEvent::OrdersCancelled { coin_pair, reports }),
+                    Err(error) => notify(SafeModeEvent::CancelFailed { coin_pair, error }),
+                }
+            }
+        }
+
+        let mut consecutive = 0;
+        while consecutive < policy.probes_to_resume {
+            tokio::time::sleep(policy.probe_interval).await;
+
+            match self.probe().await {
+                Ok(()) => {
+                    consecutive += 1;
+                    notify(SafeModeEvent::ProbeSucceeded { consecutive });
+                }
+                Err(error) => {
+                    consecutive = 0;
+                    notify(SafeModeEvent::ProbeFailed { error });
+                }
+            }
+        }
+
+        if policy.resume_trading {
+            for coin_pair in coin_pairs {
+                self.switchboard().enable(coin_pair);
+            }
+            notify(SafeModeEvent::Resumed {
+                coin_pairs: coin_pairs.iter().map(|p| p.to_string()).collect(),
+            });
+        }
+    }
+
+    /// A private read, which the TAPI answers with `TradingHalted` (199)
+    /// during a halt, unlike the public data endpoints
+    async fn probe(&self) -> Result<(), Error> {
+        self.get_account_info().await.map(|_| ())
+    }
+}
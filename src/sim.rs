@@ -0,0 +1,223 @@
+//! A deterministic, in-memory matching engine to simulate the exchange in
+//! tests and custom simulations.
+
+use crate::client::OrderType;
+use std::collections::VecDeque;
+
+/// Fee rates applied to each fill, as a fraction of the traded BRL amount
+#[derive(Debug, Clone, Copy)]
+pub struct FeeSchedule {
+    pub maker: f64,
+    pub taker: f64,
+}
+
+impl Default for FeeSchedule {
+    fn default() -> Self {
+        FeeSchedule {
+            maker: 0.003,
+            taker: 0.007,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RestingOrder {
+    pub order_id: i64,
+    pub order_type: OrderType,
+    pub limit_price: f64,
+    pub quantity: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fill {
+    pub maker_order_id: i64,
+    pub taker_order_id: i64,
+    pub price: f64,
+    pub quantity: f64,
+    /// Fee charged to the maker, in BRL
+    pub maker_fee: f64,
+    /// Fee charged to the taker, in BRL
+    pub taker_fee: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EngineEvent {
+    Accepted { order_id: i64 },
+    Filled(Fill),
+    Rested { order_id: i64, quantity: f64 },
+    Cancelled { order_id: i64, quantity: f64 },
+}
+
+/// Public: A price-time priority matching engine.
+///
+/// Orders are matched in submission order and every state change is
+/// recorded as an `EngineEvent`, so the same sequence of calls always
+/// produces the same events.
+#[derive(Debug, Default)]
+pub struct MatchingEngine {
+    fees: FeeSchedule,
+    next_order_id: i64,
+    /// Sorted by best price first, then by arrival
+    bids: VecDeque<RestingOrder>,
+    asks: VecDeque<RestingOrder>,
+    events: Vec<EngineEvent>,
+}
+
+impl MatchingEngine {
+    pub fn new(fees: FeeSchedule) -> Self {
+        MatchingEngine {
+            fees,
+            next_order_id: 1,
+            ..Default::default()
+        }
+    }
+
+    /// Submit a limit order, matching it against the book and resting any
+    /// remaining quantity. Returns the assigned order id.
+    pub fn submit_limit(&mut self, order_type: OrderType, quantity: f64, limit_price: f64) -> i64 {
+        let order_id = self.accept();
+        let remaining = self.match_order(order_id, order_type, quantity, Some(limit_price));
+
+        if remaining > 0.0 {
+            self.rest(RestingOrder {
+                order_id,
+                order_type,
+                limit_price,
+                quantity: remaining,
+            });
+        }
+
+        order_id
+    }
+
+    /// Submit a market order. Quantity that can't be matched against the
+    /// book is cancelled. Returns the assigned order id.
+    pub fn submit_market(&mut self, order_type: OrderType, quantity: f64) -> i64 {
+        let order_id = self.accept();
+        let remaining = self.match_order(order_id, order_type, quantity, None);
+
+        if remaining > 0.0 {
+            self.events.push(EngineEvent::Cancelled {
+                order_id,
+                quantity: remaining,
+            });
+        }
+
+        order_id
+    }
+
+    /// Cancel a resting order, returning it if it was still in the book
+    pub fn cancel(&mut self, order_id: i64) -> Option<RestingOrder> {
+        let side = if self.bids.iter().any(|o| o.order_id == order_id) {
+            &mut self.bids
+        } else {
+            &mut self.asks
+        };
+
+        let position = side.iter().position(|o| o.order_id == order_id)?;
+        let order = side.remove(position)?;
+
+        self.events.push(EngineEvent::Cancelled {
+            order_id,
+            quantity: order.quantity,
+        });
+
+        Some(order)
+    }
+
+    pub fn bids(&self) -> impl Iterator<Item = &RestingOrder> {
+        self.bids.iter()
+    }
+
+    pub fn asks(&self) -> impl Iterator<Item = &RestingOrder> {
+        self.asks.iter()
+    }
+
+    pub fn best_bid(&self) -> Option<f64> {
+        self.bids.front().map(|o| o.limit_price)
+    }
+
+    pub fn best_ask(&self) -> Option<f64> {
+        self.asks.front().map(|o| o.limit_price)
+    }
+
+    /// Take all events emitted since the last call
+    pub fn drain_events(&mut self) -> Vec<EngineEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    fn accept(&mut self) -> i64 {
+        let order_id = self.next_order_id.max(1);
+        self.next_order_id = order_id + 1;
+        self.events.push(EngineEvent::Accepted { order_id });
+        order_id
+    }
+
+    fn match_order(
+        &mut self,
+        taker_order_id: i64,
+        order_type: OrderType,
+        mut quantity: f64,
+        limit_price: Option<f64>,
+    ) -> f64 {
+        let book = match order_type {
+            OrderType::Buy => &mut self.asks,
+            OrderType::Sell => &mut self.bids,
+        };
+
+        while quantity > 0.0 {
+            let maker = match book.front_mut() {
+                Some(maker) => maker,
+                None => break,
+            };
+
+            let crosses = match (order_type, limit_price) {
+                (_, None) => true,
+                (OrderType::Buy, Some(limit)) => maker.limit_price <= limit,
+                (OrderType::Sell, Some(limit)) => maker.limit_price >= limit,
+            };
+            if !crosses {
+                break;
+            }
+
+            let traded = quantity.min(maker.quantity);
+            let amount = traded * maker.limit_price;
+
+            self.events.push(EngineEvent::Filled(Fill {
+                maker_order_id: maker.order_id,
+                taker_order_id,
+                price: maker.limit_price,
+                quantity: traded,
+                maker_fee: amount * self.fees.maker,
+                taker_fee: amount * self.fees.taker,
+            }));
+
+            quantity -= traded;
+            maker.quantity -= traded;
+            if maker.quantity <= 0.0 {
+                book.pop_front();
+            }
+        }
+
+        quantity
+    }
+
+    fn rest(&mut self, order: RestingOrder) {
+        let (book, better): (_, fn(f64, f64) -> bool) = match order.order_type {
+            OrderType::Buy => (&mut self.bids, |a, b| a > b),
+            OrderType::Sell => (&mut self.asks, |a, b| a < b),
+        };
+
+        // Orders at the same price keep arrival order
+        let position = book
+            .iter()
+            .position(|o| better(order.limit_price, o.limit_price))
+            .unwrap_or(book.len());
+
+        self.events.push(EngineEvent::Rested {
+            order_id: order.order_id,
+            quantity: order.quantity,
+        });
+        book.insert(position, order);
+    }
+}
@@ -0,0 +1,64 @@
+use crate::client::{
+    BalancesResponse, Client, Error, ListOrdersFilter, Order, OrderStatus, Orderbook,
+};
+use chrono::{DateTime, Duration, Utc};
+
+/// Public: Balances, open orders and orderbook of a coin pair read together,
+/// bracketed by the instants the first read started and the last one ended.
+/// Any of the reads may reflect the account state at any instant in between.
+#[derive(Debug)]
+pub struct AccountSnapshot {
+    pub balances: BalancesResponse,
+    pub open_orders: Vec<Order>,
+    pub orderbook: Orderbook,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+}
+
+impl AccountSnapshot {
+    /// Upper bound on how far apart the reads may be
+    pub fn staleness(&self) -> Duration {
+        self.finished_at - self.started_at
+    }
+}
+
+impl Client {
+    /// Read balances, open orders and the orderbook of `coin_pair`, retrying
+    /// up to `max_attempts` times while the reads take longer than
+    /// `tolerance`. Fails with `Error::StaleSnapshot` if no attempt fits.
+    pub async fn snapshot(
+        &self,
+        coin_pair: String,
+        tolerance: Duration,
+        max_attempts: u32,
+    ) -> Result<AccountSnapshot, Error> {
+        let filter = ListOrdersFilter {
+            status_list: vec![OrderStatus::Open],
+            ..Default::default()
+        };
+        let mut staleness = Duration::zero();
+
+        for _ in 0..max_attempts.max(1) {
+            let started_at = Utc::now();
+            let balances = self.get_account_info().await?.balance;
+            let open_orders = self.list_orders(coin_pair.clone(), &filter).await?.orders;
+            let orderbook = self.orderbook(coin_pair.clone(), false).await?.orderbook;
+            let finished_at = Utc::now();
+
+            let snapshot = AccountSnapshot {
+                balances,
+                open_orders,
+                orderbook,
+                started_at,
+                finished_at,
+            };
+
+            staleness = snapshot.staleness();
+            if staleness <= tolerance {
+                return Ok(snapshot);
+            }
+        }
+
+        Err(Error::StaleSnapshot(staleness))
+    }
+}
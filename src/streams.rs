@@ -0,0 +1,170 @@
+//! Combinators for streams of market data, so consumers can assemble
+//! pipelines from these pieces instead of writing ad-hoc adapters.
+
+use crate::client::{Error, Ticker};
+use crate::ws::{StreamItem, Trade};
+use futures::future;
+use futures::{Stream, StreamExt};
+use rust_decimal::Decimal;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::time::{sleep, Sleep};
+
+/// Public: Drop tickers whose last, buy and sell prices did not change since
+/// the previous item
+pub fn dedup_tickers<S>(stream: S) -> impl Stream<Item = Ticker>
+where
+    S: Stream<Item = Ticker>,
+{
+    let mut previous = None;
+
+    stream.filter(move |ticker| {
+        let current = (ticker.last, ticker.buy, ticker.sell);
+        let changed = previous != Some(current);
+        previous = Some(current);
+        future::ready(changed)
+    })
+}
+
+/// Public: Convert ticker prices on the fly, multiplying them by the rate
+/// returned by `rate` at the time each item arrives (e.g. BRL to USD)
+pub fn convert_tickers<S, F>(stream: S, mut rate: F) -> impl Stream<Item = Ticker>
+where
+    S: Stream<Item = Ticker>,
+    F: FnMut() -> f32,
+{
+    stream.map(move |mut ticker| {
+        let rate = rate();
+        ticker.high *= rate;
+        ticker.low *= rate;
+        ticker.last *= rate;
+        ticker.buy *= rate;
+        ticker.sell *= rate;
+        ticker
+    })
+}
+
+/// Public: Drop trades of a `ws::Client::trade_stream` smaller than
+/// `min_amount` of the base asset. Reconnections, stale feeds and errors
+/// pass through.
+pub fn filter_trades_by_size<S>(
+    stream: S,
+    min_amount: Decimal,
+) -> impl Stream<Item = Result<StreamItem<Trade>, Error>>
+where
+    S: Stream<Item = Result<StreamItem<Trade>, Error>>,
+{
+    stream.filter(move |item| {
+        let keep = match item {
+            Ok(StreamItem::Data(trade)) => trade.amount >= min_amount,
+            _ => true,
+        };
+        future::ready(keep)
+    })
+}
+
+/// Public: Emit at most one item per `interval`, always the most recent one.
+/// Intermediate items received while waiting are discarded.
+pub fn throttle_latest<S>(stream: S, interval: Duration) -> ThrottleLatest<S>
+where
+    S: Stream + Unpin,
+{
+    ThrottleLatest {
+        stream,
+        interval,
+        delay: None,
+        latest: None,
+        done: false,
+    }
+}
+
+pub struct ThrottleLatest<S: Stream> {
+    stream: S,
+    interval: Duration,
+    delay: Option<Pin<Box<Sleep>>>,
+    latest: Option<S::Item>,
+    done: bool,
+}
+
+impl<S> Stream for ThrottleLatest<S>
+where
+    S: Stream + Unpin,
+    S::Item: Unpin,
+{
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<S::Item>> {
+        let this = self.get_mut();
+
+        while !this.done {
+            match this.stream.poll_next_unpin(cx) {
+                Poll::Ready(Some(item)) => this.latest = Some(item),
+                Poll::Ready(None) => this.done = true,
+                Poll::Pending => break,
+            }
+        }
+
+        if let Some(delay) = this.delay.as_mut() {
+            if delay.as_mut().poll(cx).is_pending() {
+                if this.done && this.latest.is_none() {
+                    return Poll::Ready(None);
+                }
+                return Poll::Pending;
+            }
+            this.delay = None;
+        }
+
+        match this.latest.take() {
+            Some(item) => {
+                this.delay = Some(Box::pin(sleep(this.interval)));
+                Poll::Ready(Some(item))
+            }
+            None if this.done => Poll::Ready(None),
+            None => Poll::Pending,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::TradeType;
+    use chrono::Utc;
+    use futures::stream;
+
+    #[tokio::test]
+    async fn small_trades_are_dropped() {
+        let trade = |tid, amount| {
+            Ok(StreamItem::Data(Trade {
+                tid,
+                date: Utc::now(),
+                trade_type: TradeType::Buy,
+                price: Decimal::from(100),
+                amount: Decimal::new(amount, 2),
+            }))
+        };
+        let items = vec![
+            trade(1, 5),
+            trade(2, 50),
+            Ok(StreamItem::Reconnected { attempts: 1 }),
+            trade(3, 10),
+            Err(Error::UnexpectedResponse("unknown pair".to_string())),
+        ];
+
+        let kept: Vec<_> = filter_trades_by_size(stream::iter(items), Decimal::new(10, 2))
+            .collect()
+            .await;
+
+        let tids: Vec<_> = kept
+            .iter()
+            .filter_map(|item| match item {
+                Ok(StreamItem::Data(trade)) => Some(trade.tid),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(tids, vec![2, 3]);
+        assert_eq!(kept.len(), 4);
+    }
+}
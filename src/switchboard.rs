@@ -0,0 +1,43 @@
+use std::collections::HashSet;
+use std::sync::{Arc, RwLock};
+
+/// Public: Runtime switches to stop trading individual coin pairs (e.g.
+/// `BRLXRP` during an incident). Clones share the same switches, so a
+/// handle kept by the application affects every client it was given to.
+#[derive(Debug, Clone, Default)]
+pub struct Switchboard {
+    disabled: Arc<RwLock<HashSet<String>>>,
+}
+
+impl Switchboard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn disable(&self, coin_pair: &str) {
+        self.disabled
+            .write()
+            .unwrap()
+            .insert(coin_pair.to_uppercase());
+    }
+
+    pub fn enable(&self, coin_pair: &str) {
+        self.disabled
+            .write()
+            .unwrap()
+            .remove(&coin_pair.to_uppercase());
+    }
+
+    /// Replace the whole set of disabled pairs, e.g. after a config reload
+    pub fn set_disabled(&self, coin_pairs: &[&str]) {
+        *self.disabled.write().unwrap() = coin_pairs.iter().map(|p| p.to_uppercase()).collect();
+    }
+
+    pub fn is_enabled(&self, coin_pair: &str) -> bool {
+        !self
+            .disabled
+            .read()
+            .unwrap()
+            .contains(&coin_pair.to_uppercase())
+    }
+}
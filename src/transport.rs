@@ -0,0 +1,182 @@
+use crate::client::Error;
+use crate::options::request_options;
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HttpMethod {
+    Get,
+    Post,
+    Delete,
+}
+
+/// Public: A request as handed to an `HttpTransport`, with the query string
+/// already in `url`
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    pub method: HttpMethod,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<Vec<u8>>,
+    /// Overrides the transport's own timeout
+    pub timeout: Option<Duration>,
+}
+
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: Vec<u8>,
+}
+
+/// Public: Sends the HTTP requests of the clients, see
+/// `ClientBuilder::with_transport`. Implemented for `reqwest::Client`, the
+/// default, and meant to plug in another HTTP library or a test double.
+///
+/// Responses of any status are returned as is, errors are for requests
+/// that couldn't be completed at all.
+#[async_trait]
+pub trait HttpTransport: Send + Sync {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse, Error>;
+}
+
+/// Public: Hooks run around every request a client sends, e.g. for logging,
+/// metrics, injecting headers or auditing signatures, see
+/// `ClientBuilder::with_middleware`
+pub trait Middleware: Send + Sync {
+    /// Called before `request` is sent, with the API's own headers already
+    /// set
+    fn before_send(&self, _request: &mut HttpRequest) {}
+
+    /// Called once `request` completed or failed, `elapsed` after it was
+    /// sent
+    fn after_receive(
+        &self,
+        _request: &HttpRequest,
+        _response: &Result<HttpResponse, Error>,
+        _elapsed: Duration,
+    ) {
+    }
+}
+
+/// Send `request` with `transport`, running it through `middlewares` in
+/// order before sending and in reverse order after receiving
+pub(crate) async fn send_through(
+    transport: &dyn HttpTransport,
+    middlewares: &[Arc<dyn Middleware>],
+    mut request: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    if middlewares.is_empty() {
+        return transport.send(request).await;
+    }
+
+    for middleware in middlewares {
+        middleware.before_send(&mut request);
+    }
+
+    let sent = request.clone();
+    let start = Instant::now();
+    let response = transport.send(request).await;
+    let elapsed = start.elapsed();
+
+    for middleware in middlewares.iter().rev() {
+        middleware.after_receive(&sent, &response, elapsed);
+    }
+
+    response
+}
+
+impl HttpRequest {
+    /// A request with the `RequestOptions` of the current task applied
+    pub fn new(method: HttpMethod, url: String) -> Self {
+        HttpRequest {
+            method,
+            url,
+            headers: Vec::new(),
+            body: None,
+            timeout: request_options().timeout,
+        }
+    }
+
+    pub fn get(url: String) -> Self {
+        Self::new(HttpMethod::Get, url)
+    }
+
+    pub fn post(url: String) -> Self {
+        Self::new(HttpMethod::Post, url)
+    }
+
+    pub fn delete(url: String) -> Self {
+        Self::new(HttpMethod::Delete, url)
+    }
+
+    /// Append `query` to the url, url encoded
+    pub fn with_query<Q: Serialize + ?Sized>(mut self, query: &Q) -> Self {
+        let query = serde_urlencoded::to_string(query).unwrap();
+        if !query.is_empty() {
+            let separator = if self.url.contains('?') { '&' } else { '?' };
+            self.url = format!("{}{}{}", self.url, separator, query);
+        }
+        self
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn with_json<B: Serialize + ?Sized>(self, body: &B) -> Self {
+        let body = serde_json::to_vec(body).unwrap();
+        self.with_body("application/json", body)
+    }
+
+    pub fn with_form<B: Serialize + ?Sized>(self, body: &B) -> Self {
+        let body = serde_urlencoded::to_string(body).unwrap();
+        self.with_body("application/x-www-form-urlencoded", body.into_bytes())
+    }
+
+    fn with_body(mut self, content_type: &str, body: Vec<u8>) -> Self {
+        self.body = Some(body);
+        self.with_header("Content-Type", content_type)
+    }
+}
+
+impl HttpResponse {
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, Error> {
+        serde_json::from_slice(&self.body).map_err(Error::DecodeError)
+    }
+}
+
+#[async_trait]
+impl HttpTransport for reqwest::Client {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse, Error> {
+        let method = match request.method {
+            HttpMethod::Get => reqwest::Method::GET,
+            HttpMethod::Post => reqwest::Method::POST,
+            HttpMethod::Delete => reqwest::Method::DELETE,
+        };
+
+        let mut builder = self.request(method, &request.url);
+        for (name, value) in &request.headers {
+            builder = builder.header(name.as_str(), value.as_str());
+        }
+        if let Some(body) = request.body {
+            builder = builder.body(body);
+        }
+        if let Some(timeout) = request.timeout {
+            builder = builder.timeout(timeout);
+        }
+
+        let response = builder.send().await?;
+        Ok(HttpResponse {
+            status: response.status().as_u16(),
+            body: response.bytes().await?.to_vec(),
+        })
+    }
+}
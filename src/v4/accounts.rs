@@ -0,0 +1,95 @@
+use super::Client;
+use crate::client::{from_str, Error};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Account {
+    pub id: String,
+    pub name: String,
+    /// e.g. `live`
+    #[serde(rename = "type")]
+    pub account_type: String,
+    pub currency: String,
+    pub currency_sign: String,
+}
+
+impl Client {
+    /// List the accounts of the authorized user. Every other private route
+    /// is scoped by one of their ids.
+    /// See docs: https://api.mercadobitcoin.net/api/v4/docs#tag/Account
+    pub async fn list_accounts(&self) -> Result<Vec<Account>, Error> {
+        self.get("/accounts", &[]).await
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Balance {
+    /// e.g. `BTC`, `USDT`, `PAXG`
+    pub symbol: String,
+    #[serde(deserialize_with = "from_str")]
+    pub available: f64,
+    #[serde(deserialize_with = "from_str")]
+    pub on_hold: f64,
+    #[serde(deserialize_with = "from_str")]
+    pub total: f64,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Position {
+    pub id: String,
+    /// e.g. `BTC-BRL`
+    pub instrument: String,
+    pub side: String,
+    pub category: String,
+    #[serde(deserialize_with = "from_str")]
+    pub qty: f64,
+    pub avg_price: f64,
+}
+
+impl Client {
+    /// Balances of every asset held in the account
+    /// See docs: https://api.mercadobitcoin.net/api/v4/docs#tag/Account
+    pub async fn balances(&self, account_id: &str) -> Result<Vec<Balance>, Error> {
+        self.get(&format!("/accounts/{}/balances", account_id), &[])
+            .await
+    }
+
+    /// Open positions of the account in a symbol, e.g. `BTC-BRL`
+    /// See docs: https://api.mercadobitcoin.net/api/v4/docs#tag/Account
+    pub async fn positions(&self, account_id: &str, symbol: &str) -> Result<Vec<Position>, Error> {
+        let query = [("symbols", symbol.to_string())];
+        self.get(&format!("/accounts/{}/positions", account_id), &query)
+            .await
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct TradingFees {
+    /// e.g. `BTC`
+    pub base: String,
+    /// e.g. `BRL`
+    pub quote: String,
+    /// Rate charged when providing liquidity, as a fraction (0.003 = 0.3%)
+    pub maker_fee: Decimal,
+    /// Rate charged when taking liquidity
+    pub taker_fee: Decimal,
+    /// Fee tier of the account, when reported
+    #[serde(default)]
+    pub tier: Option<String>,
+    /// Traded volume of the last 30 days the tier is based on, in the quote
+    /// asset, when reported
+    #[serde(default, rename = "volume_30d")]
+    pub volume_30_days: Option<Decimal>,
+}
+
+impl Client {
+    /// Maker and taker rates the account pays in a symbol, e.g. `BTC-BRL`
+    /// See docs: https://api.mercadobitcoin.net/api/v4/docs#tag/Account
+    pub async fn trading_fees(&self, account_id: &str, symbol: &str) -> Result<TradingFees, Error> {
+        self.get(&format!("/accounts/{}/{}/fees", account_id, symbol), &[])
+            .await
+    }
+}
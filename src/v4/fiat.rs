@@ -0,0 +1,114 @@
+use super::pagination::{next_page, paginate, Paginated};
+use super::{Client, Withdrawal};
+use crate::client::{from_str, Error, ParamFormat};
+use chrono::serde::ts_seconds;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A bank account registered for BRL withdrawals
+#[derive(Deserialize, Debug, Clone)]
+pub struct BankAccount {
+    /// Identifies the account in `FiatWithdrawalRequest`
+    pub account_ref: i64,
+    pub bank_code: Option<String>,
+    pub bank_name: Option<String>,
+    pub agency: Option<String>,
+    pub account_number: Option<String>,
+    pub account_type: Option<String>,
+    /// Set for accounts reached through a PIX key
+    pub pix_key: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct FiatDeposit {
+    pub id: String,
+    #[serde(deserialize_with = "from_str")]
+    pub amount: f64,
+    /// e.g. `completed`
+    pub status: String,
+    /// e.g. `pix`, `ted`
+    pub source: Option<String>,
+    #[serde(with = "ts_seconds")]
+    pub created_at: DateTime<Utc>,
+}
+
+/// Public: A BRL withdrawal to a registered bank account, see
+/// `Client::withdraw_brl`
+#[derive(Serialize, Debug, Clone)]
+pub struct FiatWithdrawalRequest {
+    account_ref: i64,
+    quantity: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+}
+
+impl FiatWithdrawalRequest {
+    /// Withdraw `amount` BRL, written with 2 decimals, failing with
+    /// `Error::InvalidOrderParam` for values that can't be
+    pub fn new(account_ref: i64, amount: f64) -> Result<Self, Error> {
+        Ok(FiatWithdrawalRequest {
+            account_ref,
+            quantity: ParamFormat::SmallestUnit.format(amount, 2)?,
+            description: None,
+        })
+    }
+
+    pub fn with_description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+}
+
+impl Client {
+    /// Bank accounts and PIX keys BRL can be withdrawn to
+    /// See docs: https://api.mercadobitcoin.net/api/v4/docs#tag/Wallet
+    pub async fn list_bank_accounts(&self, account_id: &str) -> Result<Vec<BankAccount>, Error> {
+        let path = format!("/accounts/{}/wallet/withdraw/bank-accounts", account_id);
+        self.get(&path, &[]).await
+    }
+
+    /// BRL deposits received by PIX or bank transfer, newest first. `page`
+    /// starts at 1.
+    /// See docs: https://api.mercadobitcoin.net/api/v4/docs#tag/Wallet
+    pub async fn list_fiat_deposits(
+        &self,
+        account_id: &str,
+        page: u32,
+        limit: u32,
+    ) -> Result<Vec<FiatDeposit>, Error> {
+        let path = format!("/accounts/{}/wallet/fiat/BRL/deposits", account_id);
+        let query = [("page", page.to_string()), ("limit", limit.to_string())];
+        self.get(&path, &query).await
+    }
+
+    /// Withdraw BRL to a registered bank account. Like crypto withdrawals it
+    /// can be listed and polled with `list_withdrawals` and `get_withdrawal`
+    /// using the `BRL` symbol.
+    /// See docs: https://api.mercadobitcoin.net/api/v4/docs#tag/Wallet
+    pub async fn withdraw_brl(
+        &self,
+        account_id: &str,
+        request: &FiatWithdrawalRequest,
+    ) -> Result<Withdrawal, Error> {
+        let path = format!("/accounts/{}/wallet/BRL/withdraw", account_id);
+        self.post(&path, request).await
+    }
+}
+
+impl Client {
+    /// Stream every BRL deposit, newest first, `page_size` at a time
+    pub fn fiat_deposits(&self, account_id: &str, page_size: u32) -> Paginated<'_, FiatDeposit> {
+        let account_id = account_id.to_string();
+
+        paginate(1, move |page| {
+            let account_id = account_id.clone();
+            async move {
+                let deposits = self
+                    .list_fiat_deposits(&account_id, page, page_size)
+                    .await?;
+                let next = next_page(page, deposits.len(), page_size);
+                Ok((deposits, next))
+            }
+        })
+    }
+}
@@ -0,0 +1,165 @@
+use super::Client;
+use crate::client::{from_str, Error, Resolution, TradeType};
+use chrono::serde::ts_seconds;
+use chrono::{DateTime, TimeZone, Utc};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Ticker {
+    /// e.g. `BTC-BRL`
+    pub pair: String,
+    #[serde(deserialize_with = "from_str")]
+    pub high: f64,
+    #[serde(deserialize_with = "from_str")]
+    pub low: f64,
+    #[serde(deserialize_with = "from_str")]
+    pub vol: f64,
+    #[serde(deserialize_with = "from_str")]
+    pub last: f64,
+    #[serde(deserialize_with = "from_str")]
+    pub buy: f64,
+    #[serde(deserialize_with = "from_str")]
+    pub sell: f64,
+    #[serde(deserialize_with = "from_str")]
+    pub open: f64,
+    #[serde(with = "ts_seconds")]
+    pub date: DateTime<Utc>,
+}
+
+impl Client {
+    /// Tickers of several symbols, e.g. `BTC-BRL`, in a single request
+    /// See docs: https://api.mercadobitcoin.net/api/v4/docs#tag/Public-Data
+    pub async fn tickers(&self, symbols: &[&str]) -> Result<Vec<Ticker>, Error> {
+        let query = [("symbols", symbols.join(","))];
+        self.public_get("/tickers", &query).await
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(from = "(Decimal, Decimal)")]
+pub struct PriceLevel {
+    pub price: Decimal,
+    pub quantity: Decimal,
+}
+
+impl From<(Decimal, Decimal)> for PriceLevel {
+    fn from((price, quantity): (Decimal, Decimal)) -> Self {
+        PriceLevel { price, quantity }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Orderbook {
+    /// Best (lowest) price first
+    pub asks: Vec<PriceLevel>,
+    /// Best (highest) price first
+    pub bids: Vec<PriceLevel>,
+    /// As sent by the exchange, increases with every book update
+    pub timestamp: i64,
+}
+
+impl Client {
+    /// Public orderbook of a symbol, e.g. `BTC-BRL`. `limit` keeps only the
+    /// top levels of each side, the exchange default is 1000.
+    /// See docs: https://api.mercadobitcoin.net/api/v4/docs#tag/Public-Data
+    pub async fn orderbook(&self, symbol: &str, limit: Option<u32>) -> Result<Orderbook, Error> {
+        let query: Vec<_> = limit
+            .map(|limit| ("limit", limit.to_string()))
+            .into_iter()
+            .collect();
+        self.public_get(&format!("/{}/orderbook", symbol), &query)
+            .await
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Trade {
+    pub tid: i64,
+    #[serde(with = "ts_seconds")]
+    pub date: DateTime<Utc>,
+    #[serde(rename = "type")]
+    pub trade_type: TradeType,
+    pub price: Decimal,
+    pub amount: Decimal,
+}
+
+impl Client {
+    /// Latest public trades of a symbol, e.g. `BTC-BRL`. `limit` caps how
+    /// many are returned.
+    /// See docs: https://api.mercadobitcoin.net/api/v4/docs#tag/Public-Data
+    pub async fn trades(&self, symbol: &str, limit: Option<u32>) -> Result<Vec<Trade>, Error> {
+        let query: Vec<_> = limit
+            .map(|limit| ("limit", limit.to_string()))
+            .into_iter()
+            .collect();
+        self.public_get(&format!("/{}/trades", symbol), &query)
+            .await
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candle {
+    /// When the candle opens
+    pub time: DateTime<Utc>,
+    pub open: Decimal,
+    pub high: Decimal,
+    pub low: Decimal,
+    pub close: Decimal,
+    pub volume: Decimal,
+}
+
+/// The candles endpoint answers with one array per field
+#[derive(Deserialize)]
+struct CandlesResponse {
+    t: Vec<i64>,
+    o: Vec<Decimal>,
+    h: Vec<Decimal>,
+    l: Vec<Decimal>,
+    c: Vec<Decimal>,
+    v: Vec<Decimal>,
+}
+
+impl Client {
+    /// OHLCV candles of a symbol, e.g. `BTC-BRL`, up to `to`. With
+    /// `countback` the last that many candles are returned regardless of
+    /// `from`.
+    /// See docs: https://api.mercadobitcoin.net/api/v4/docs#tag/Public-Data
+    pub async fn candles(
+        &self,
+        symbol: &str,
+        resolution: Resolution,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        countback: Option<u32>,
+    ) -> Result<Vec<Candle>, Error> {
+        let mut query = vec![
+            ("symbol", symbol.to_string()),
+            ("resolution", resolution.as_str().to_string()),
+            ("from", from.timestamp().to_string()),
+            ("to", to.timestamp().to_string()),
+        ];
+        if let Some(countback) = countback {
+            query.push(("countback", countback.to_string()));
+        }
+        let response: CandlesResponse = self.public_get("/candles", &query).await?;
+
+        let candles = response
+            .t
+            .iter()
+            .enumerate()
+            .filter_map(|(i, time)| {
+                Some(Candle {
+                    time: Utc.timestamp_opt(*time, 0).single()?,
+                    open: *response.o.get(i)?,
+                    high: *response.h.get(i)?,
+                    low: *response.l.get(i)?,
+                    close: *response.c.get(i)?,
+                    volume: *response.v.get(i)?,
+                })
+            })
+            .collect();
+
+        Ok(candles)
+    }
+}
@@ -0,0 +1,385 @@
+//! Client for the v4 generation of the Mercado Bitcoin API, which
+//! authenticates with short-lived bearer tokens instead of signing each
+//! request.
+//! See docs: https://api.mercadobitcoin.net/api/v4/docs
+
+mod accounts;
+mod fiat;
+mod market;
+mod pagination;
+mod polling;
+mod trading;
+mod wallet;
+
+pub use crate::client::Resolution;
+pub use accounts::{Account, Balance, Position, TradingFees};
+pub use fiat::{BankAccount, FiatDeposit, FiatWithdrawalRequest};
+pub use market::{Candle, Orderbook, PriceLevel, Ticker, Trade};
+pub use pagination::Paginated;
+pub use rust_decimal::Decimal;
+pub use trading::{
+    CancelledOrder, Execution, Liquidity, ListOrdersFilter, Order, OrderKind, OrderRequest,
+    OrderStatus, PlacedOrder, Side,
+};
+pub use wallet::{
+    DepositAddress, DepositExtra, QrCode, Withdrawal, WithdrawalRequest, WithdrawalStatus,
+};
+
+use crate::client::{Error, DEFAULT_USER_AGENT};
+use crate::currency::Currency;
+use crate::risk::{self, ExposureLimits};
+use crate::switchboard::Switchboard;
+use crate::transport::{self, HttpRequest, HttpTransport, Middleware};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Tokens are refreshed this long before they expire
+const TOKEN_REFRESH_MARGIN_SECS: i64 = 60;
+
+/// Error body returned by v4 endpoints
+#[derive(Deserialize, Debug, Clone)]
+pub struct ApiError {
+    #[serde(skip)]
+    pub status: u16,
+    pub code: String,
+    pub message: String,
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}: {}", self.status, self.code, self.message)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    access_token: String,
+    expires_at: DateTime<Utc>,
+}
+
+impl Token {
+    fn is_fresh(&self) -> bool {
+        Utc::now() + Duration::seconds(TOKEN_REFRESH_MARGIN_SECS) < self.expires_at
+    }
+}
+
+#[derive(Serialize)]
+struct AuthorizeRequest<'a> {
+    login: &'a str,
+    password: &'a str,
+}
+
+#[derive(Deserialize)]
+struct AuthorizeResponse {
+    access_token: String,
+    expiration: i64,
+}
+
+/// Public: A bearer token shared by every clone, so several clients using
+/// the same credentials authorize only once
+#[derive(Debug, Clone, Default)]
+pub struct TokenCache {
+    token: Arc<Mutex<Option<Token>>>,
+}
+
+impl TokenCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Public: The v4 client, authorized with an API key id (`login`) and
+/// secret (`password`). Clones share the connection pool and the token.
+#[derive(Clone)]
+pub struct Client {
+    url: String,
+    login: String,
+    password: String,
+    token_cache: TokenCache,
+    /// Shared by every request, reusing pooled connections and TLS sessions
+    transport: Arc<dyn HttpTransport>,
+    user_agent: String,
+    headers: Vec<(String, String)>,
+    middlewares: Vec<Arc<dyn Middleware>>,
+    switchboard: Switchboard,
+    exposure_limits: Option<ExposureLimits>,
+}
+
+/// The coin pair of a symbol, e.g. `BRLBTC` for `BTC-BRL`
+pub(crate) fn coin_pair(symbol: &str) -> String {
+    symbol.to_uppercase().rsplit('-').collect()
+}
+
+/// The asset bought and sold in a symbol, e.g. BTC for `BTC-BRL`
+fn base_asset(symbol: &str) -> Currency {
+    symbol
+        .split('-')
+        .next()
+        .unwrap_or_default()
+        .parse()
+        .unwrap()
+}
+
+/// The symbol an order is placed in by POSTing to `path`, which is
+/// `/accounts/<id>/<symbol>/orders`
+fn placement_symbol(path: &str) -> Option<&str> {
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    match segments[..] {
+        ["accounts", _, symbol, "orders"] => Some(symbol),
+        _ => None,
+    }
+}
+
+impl Client {
+    /// Public: Function used to initialize a v4 client, `url` being the API
+    /// root, e.g. `https://api.mercadobitcoin.net/api/v4`
+    pub fn init(url: String, login: String, password: String) -> Self {
+        Client {
+            url,
+            login,
+            password,
+            token_cache: TokenCache::new(),
+            transport: Arc::new(reqwest::Client::new()),
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            headers: Vec::new(),
+            middlewares: Vec::new(),
+            switchboard: Switchboard::new(),
+            exposure_limits: None,
+        }
+    }
+
+    /// Public: Send requests with `client`, e.g. to share an application's
+    /// connection pool, proxies or root certificates
+    pub fn with_http_client(self, client: reqwest::Client) -> Self {
+        self.with_transport(Arc::new(client))
+    }
+
+    /// Public: Send requests with `transport` instead of reqwest
+    pub fn with_transport(mut self, transport: Arc<dyn HttpTransport>) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    /// Public: Identify the application in the `User-Agent` header of every
+    /// request. Defaults to `DEFAULT_USER_AGENT`.
+    pub fn with_user_agent(mut self, user_agent: String) -> Self {
+        self.user_agent = user_agent;
+        self
+    }
+
+    /// Public: Send a header with every request, on top of the ones the API
+    /// requires
+    pub fn with_header(mut self, name: String, value: String) -> Self {
+        self.headers.push((name, value));
+        self
+    }
+
+    /// Public: Run `middleware` around every request, after the ones added
+    /// before it
+    pub fn with_middleware(mut self, middleware: Arc<dyn Middleware>) -> Self {
+        self.middlewares.push(middleware);
+        self
+    }
+
+    /// Public: Use a shared switchboard to enable or disable trading per
+    /// pair. Give it the one of a v3 client to switch a pair off in both.
+    pub fn with_switchboard(mut self, switchboard: Switchboard) -> Self {
+        self.switchboard = switchboard;
+        self
+    }
+
+    /// Public: The switchboard checked before every order placement, keyed
+    /// by coin pair (`BRLBTC` for `BTC-BRL`)
+    pub fn switchboard(&self) -> &Switchboard {
+        &self.switchboard
+    }
+
+    /// Public: Enforce exposure limits on every buy placed, sharing them
+    /// (and the exposure) with any other client given the same limits. An
+    /// order the limits would shrink is rejected, as its request can't be
+    /// changed.
+    pub fn with_exposure_limits(mut self, limits: ExposureLimits) -> Self {
+        self.exposure_limits = Some(limits);
+        self
+    }
+
+    /// Public: The exposure limits enforced on buys, if any
+    pub fn exposure_limits(&self) -> Option<&ExposureLimits> {
+        self.exposure_limits.as_ref()
+    }
+
+    /// Quantity of the order in `body` admitted by the exposure limits. A
+    /// market buy for a cost is priced at the best ask.
+    async fn admit_order<B: Serialize>(&self, symbol: &str, body: &B) -> Result<f64, Error> {
+        let limits = match &self.exposure_limits {
+            Some(limits) => limits,
+            None => return Ok(0.0),
+        };
+        let order = serde_json::to_value(body).map_err(Error::DecodeError)?;
+        if order["side"] != "buy" {
+            return Ok(0.0);
+        }
+
+        let amount = |field: &str| order[field].as_str().and_then(|a| a.parse::<f64>().ok());
+        let quantity = match (amount("qty"), amount("cost")) {
+            (Some(qty), _) => Some(qty),
+            (None, Some(cost)) => {
+                let ask = match self.tickers(&[symbol]).await?.pop() {
+                    Some(ticker) if ticker.sell > 0.0 => ticker.sell,
+                    _ => {
+                        return Err(Error::ExposureLimitExceeded(format!(
+                            "no {} ask to price a market buy at",
+                            symbol
+                        )))
+                    }
+                };
+                Some(cost / ask)
+            }
+            (None, None) => None,
+        };
+        let quantity = quantity.ok_or_else(|| {
+            Error::ExposureLimitExceeded(format!("{} order without a quantity", symbol))
+        })?;
+
+        let asset = base_asset(symbol);
+        let admitted = limits.admit_buy(&asset, quantity)?;
+        if admitted < quantity {
+            limits.release_buy(&asset, admitted);
+            return Err(Error::ExposureLimitExceeded(format!(
+                "{} {} over the limit",
+                quantity, asset
+            )));
+        }
+        Ok(admitted)
+    }
+
+    /// Give back the exposure of an order that certainly wasn't placed
+    fn release_order(&self, symbol: &str, admitted: f64) {
+        if let Some(limits) = &self.exposure_limits {
+            limits.release_buy(&base_asset(symbol), admitted);
+        }
+    }
+
+    /// Public: Share a token cache with other clients using the same credentials
+    pub fn with_token_cache(mut self, token_cache: TokenCache) -> Self {
+        self.token_cache = token_cache;
+        self
+    }
+
+    /// A valid access token, authorizing again when the cached one is
+    /// missing or about to expire
+    /// See docs: https://api.mercadobitcoin.net/api/v4/docs#tag/Authorization
+    pub async fn access_token(&self) -> Result<String, Error> {
+        let mut token = self.token_cache.token.lock().await;
+
+        if let Some(token) = token.as_ref().filter(|token| token.is_fresh()) {
+            return Ok(token.access_token.clone());
+        }
+
+        let request =
+            HttpRequest::post(format!("{}/authorize", self.url)).with_json(&AuthorizeRequest {
+                login: &self.login,
+                password: &self.password,
+            });
+        let response: AuthorizeResponse = self.send(request).await?;
+
+        let expires_at = Utc
+            .timestamp_opt(response.expiration, 0)
+            .single()
+            .unwrap_or_else(Utc::now);
+        *token = Some(Token {
+            access_token: response.access_token.clone(),
+            expires_at,
+        });
+
+        Ok(response.access_token)
+    }
+
+    async fn send<T: DeserializeOwned>(&self, mut request: HttpRequest) -> Result<T, Error> {
+        request.headers.extend(self.headers.iter().cloned());
+        request
+            .headers
+            .push(("User-Agent".to_string(), self.user_agent.clone()));
+        let response =
+            transport::send_through(&*self.transport, &self.middlewares, request).await?;
+
+        if response.is_success() {
+            return response.json();
+        }
+
+        let mut error = response.json::<ApiError>()?;
+        error.status = response.status;
+        Err(Error::V4ApiError(error))
+    }
+
+    async fn authorized<T: DeserializeOwned>(&self, request: HttpRequest) -> Result<T, Error> {
+        let token = self.access_token().await?;
+        let authorization = format!("Bearer {}", token);
+        self.send(request.with_header("Authorization", &authorization))
+            .await
+    }
+
+    /// GET a public endpoint, which doesn't need a token
+    async fn public_get<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<T, Error> {
+        let request = HttpRequest::get(format!("{}{}", self.url, path)).with_query(query);
+        self.send(request).await
+    }
+
+    /// Call any authorized GET endpoint, `path` being relative to the API
+    /// root. Useful for endpoints that don't have a typed wrapper yet.
+    pub async fn get<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<T, Error> {
+        let request = HttpRequest::get(format!("{}{}", self.url, path)).with_query(query);
+        self.authorized(request).await
+    }
+
+    /// Call any authorized POST endpoint with a JSON body. Order placements
+    /// are checked against the switchboard and the exposure limits.
+    pub async fn post<T: DeserializeOwned, B: Serialize>(
+        &self,
+        path: &str,
+        body: &B,
+    ) -> Result<T, Error> {
+        let symbol = placement_symbol(path);
+        let admitted = match symbol {
+            Some(symbol) => {
+                let coin_pair = coin_pair(symbol);
+                if !self.switchboard.is_enabled(&coin_pair) {
+                    return Err(Error::TradingDisabled(coin_pair));
+                }
+                self.admit_order(symbol, body).await?
+            }
+            None => 0.0,
+        };
+
+        let request = HttpRequest::post(format!("{}{}", self.url, path)).with_json(body);
+        let response = self.authorized(request).await;
+        if let (Err(e), Some(symbol)) = (&response, symbol) {
+            if risk::is_rejection(e) {
+                self.release_order(symbol, admitted);
+            }
+        }
+        response
+    }
+
+    /// Call any authorized DELETE endpoint
+    pub async fn delete<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<T, Error> {
+        let request = HttpRequest::delete(format!("{}{}", self.url, path)).with_query(query);
+        self.authorized(request).await
+    }
+}
@@ -0,0 +1,66 @@
+use crate::client::Error;
+use futures::{stream, Stream, TryStreamExt};
+use std::future::Future;
+use std::pin::Pin;
+
+/// Public: Every item of a v4 listing, fetching the next page once the
+/// current one is consumed. `Send`, so it can be consumed in a spawned task.
+pub type Paginated<'a, T> = Pin<Box<dyn Stream<Item = Result<T, Error>> + Send + 'a>>;
+
+/// The items of a page and the cursor of the next one, `None` on the last
+pub(crate) type Page<T, C> = (Vec<T>, Option<C>);
+
+/// Stream the items of the pages returned by `fetch`, starting at `first`
+pub(crate) fn paginate<'a, T, C, F, Fut>(first: C, mut fetch: F) -> Paginated<'a, T>
+where
+    T: Send + 'a,
+    C: Send + 'a,
+    F: FnMut(C) -> Fut + Send + 'a,
+    Fut: Future<Output = Result<Page<T, C>, Error>> + Send + 'a,
+{
+    let pages = stream::try_unfold(Some(first), move |cursor| {
+        let page = cursor.map(&mut fetch);
+        async move {
+            let (items, next) = match page {
+                Some(page) => page.await?,
+                None => return Ok::<_, Error>(None),
+            };
+            Ok(Some((stream::iter(items.into_iter().map(Ok)), next)))
+        }
+    });
+
+    Box::pin(pages.try_flatten())
+}
+
+/// Cursor of numbered pages, starting at 1: a short page is the last one
+pub(crate) fn next_page(page: u32, items: usize, page_size: u32) -> Option<u32> {
+    if items < page_size as usize {
+        None
+    } else {
+        Some(page + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn pages_are_consumed_in_a_spawned_task() {
+        let items: Vec<u32> = (1..=5).collect();
+        let task = tokio::spawn(async move {
+            let pages = paginate(1, |page| {
+                let items = items.clone();
+                async move {
+                    let start = (page as usize - 1) * 2;
+                    let page_items: Vec<u32> = items.into_iter().skip(start).take(2).collect();
+                    let next = next_page(page, page_items.len(), 2);
+                    Ok((page_items, next))
+                }
+            });
+            pages.try_collect::<Vec<_>>().await
+        });
+
+        assert_eq!(task.await.unwrap().unwrap(), vec![1, 2, 3, 4, 5]);
+    }
+}
@@ -0,0 +1,88 @@
+//! Streams built by polling the REST API, yielding the same events as the
+//! websocket feed for environments where websockets are blocked.
+
+use super::{coin_pair, Client, Ticker};
+use crate::client::Error;
+use crate::ws::{self, Event};
+use futures::{stream, Stream};
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use std::future::Future;
+use std::time::Duration;
+use tokio::time::{interval, MissedTickBehavior};
+
+impl From<&Ticker> for ws::Ticker {
+    fn from(ticker: &Ticker) -> Self {
+        let decimal = |value: f64| Decimal::from_f64(value).unwrap_or_default();
+
+        ws::Ticker {
+            high: decimal(ticker.high),
+            low: decimal(ticker.low),
+            vol: decimal(ticker.vol),
+            last: decimal(ticker.last),
+            buy: decimal(ticker.buy),
+            sell: decimal(ticker.sell),
+            open: decimal(ticker.open),
+            date: ticker.date,
+        }
+    }
+}
+
+impl Client {
+    /// `Event::Ticker`s of a symbol, e.g. `BTC-BRL`, fetched every `period`.
+    /// Failed polls are yielded as errors and polling goes on.
+    pub fn poll_ticker_stream<'a>(
+        &'a self,
+        symbol: &'a str,
+        period: Duration,
+    ) -> impl Stream<Item = Result<Event, Error>> + 'a {
+        poll(period, move || async move {
+            let ticker = self
+                .tickers(&[symbol])
+                .await?
+                .pop()
+                .ok_or_else(|| Error::UnexpectedResponse(format!("no {} ticker", symbol)))?;
+
+            Ok(Event::Ticker {
+                coin_pair: coin_pair(symbol),
+                ticker: ws::Ticker::from(&ticker),
+            })
+        })
+    }
+
+    /// `Event::Orderbook`s of a symbol, e.g. `BTC-BRL`, with `limit` levels
+    /// per side, fetched every `period`. Failed polls are yielded as errors
+    /// and polling goes on.
+    pub fn poll_orderbook_stream<'a>(
+        &'a self,
+        symbol: &'a str,
+        limit: Option<u32>,
+        period: Duration,
+    ) -> impl Stream<Item = Result<Event, Error>> + 'a {
+        poll(period, move || async move {
+            let orderbook = self.orderbook(symbol, limit).await?;
+
+            Ok(Event::Orderbook {
+                coin_pair: coin_pair(symbol),
+                orderbook: ws::Orderbook::from(&orderbook),
+            })
+        })
+    }
+}
+
+/// Call `fetch` right away and then every `period`, skipping ticks missed
+/// while a slow request was in flight
+fn poll<F, Fut>(period: Duration, fetch: F) -> impl Stream<Item = Result<Event, Error>>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<Event, Error>>,
+{
+    let mut ticks = interval(period);
+    ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    stream::unfold((ticks, fetch), |(mut ticks, fetch)| async move {
+        ticks.tick().await;
+        let event = fetch().await;
+        Some((event, (ticks, fetch)))
+    })
+}
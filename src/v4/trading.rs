@@ -0,0 +1,449 @@
+use super::pagination::{paginate, Paginated};
+use super::Client;
+use crate::client::{from_str, Error, ParamFormat};
+use chrono::serde::ts_seconds;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderKind {
+    #[serde(rename = "market")]
+    Market,
+    #[serde(rename = "limit")]
+    Limit,
+    #[serde(rename = "stoplimit")]
+    StopLimit,
+    /// A limit order that is cancelled instead of taking liquidity
+    #[serde(rename = "post-only")]
+    PostOnly,
+}
+
+/// Public: An order to place with `Client::place_order`, built from one of
+/// the order type constructors and refined with the `with_*` methods
+#[derive(Debug, Clone)]
+pub struct OrderRequest {
+    side: Side,
+    kind: OrderKind,
+    qty: Option<f64>,
+    cost: Option<f64>,
+    limit_price: Option<f64>,
+    stop_price: Option<f64>,
+    external_id: Option<String>,
+    asynchronous: Option<bool>,
+}
+
+/// An `OrderRequest` as sent, amounts written as decimal strings
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct OrderBody<'a> {
+    side: Side,
+    #[serde(rename = "type")]
+    kind: OrderKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    qty: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cost: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    limit_price: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop_price: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    external_id: Option<&'a str>,
+    #[serde(rename = "async", skip_serializing_if = "Option::is_none")]
+    asynchronous: Option<bool>,
+}
+
+impl OrderRequest {
+    fn new(side: Side, kind: OrderKind) -> Self {
+        OrderRequest {
+            side,
+            kind,
+            qty: None,
+            cost: None,
+            limit_price: None,
+            stop_price: None,
+            external_id: None,
+            asynchronous: None,
+        }
+    }
+
+    /// A market order for `quantity` units of the base asset
+    pub fn market(side: Side, quantity: f64) -> Self {
+        Self::new(side, OrderKind::Market).with_quantity(quantity)
+    }
+
+    /// A market order spending `cost` in the quote asset
+    pub fn market_cost(side: Side, cost: f64) -> Self {
+        let mut request = Self::new(side, OrderKind::Market);
+        request.cost = Some(cost);
+        request
+    }
+
+    pub fn limit(side: Side, quantity: f64, limit_price: f64) -> Self {
+        let mut request = Self::new(side, OrderKind::Limit).with_quantity(quantity);
+        request.limit_price = Some(limit_price);
+        request
+    }
+
+    /// A limit order that only rests in the book once `stop_price` trades
+    pub fn stop_limit(side: Side, quantity: f64, limit_price: f64, stop_price: f64) -> Self {
+        let mut request = Self::limit(side, quantity, limit_price);
+        request.kind = OrderKind::StopLimit;
+        request.stop_price = Some(stop_price);
+        request
+    }
+
+    pub fn post_only(side: Side, quantity: f64, limit_price: f64) -> Self {
+        let mut request = Self::limit(side, quantity, limit_price);
+        request.kind = OrderKind::PostOnly;
+        request
+    }
+
+    pub fn with_quantity(mut self, quantity: f64) -> Self {
+        self.qty = Some(quantity);
+        self
+    }
+
+    /// An id of the caller's choosing to find the order later
+    pub fn with_external_id(mut self, external_id: &str) -> Self {
+        self.external_id = Some(external_id.to_string());
+        self
+    }
+
+    /// Return as soon as the order is accepted instead of waiting for it
+    /// to be processed
+    pub fn asynchronous(mut self) -> Self {
+        self.asynchronous = Some(true);
+        self
+    }
+
+    /// The body to send, quantities written with 8 decimals and BRL amounts
+    /// with 2 the way v3 order params are, failing with
+    /// `Error::InvalidOrderParam` for values that can't be
+    fn body(&self) -> Result<OrderBody<'_>, Error> {
+        let format = |value: Option<f64>, decimals| {
+            value
+                .map(|value| ParamFormat::SmallestUnit.format(value, decimals))
+                .transpose()
+        };
+
+        Ok(OrderBody {
+            side: self.side,
+            kind: self.kind,
+            qty: format(self.qty, 8)?,
+            cost: format(self.cost, 2)?,
+            limit_price: format(self.limit_price, 2)?,
+            stop_price: format(self.stop_price, 2)?,
+            external_id: self.external_id.as_deref(),
+            asynchronous: self.asynchronous,
+        })
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PlacedOrder {
+    pub order_id: String,
+}
+
+impl Client {
+    /// Place an order in a symbol, e.g. `BTC-BRL`, unless the switchboard
+    /// has its pair switched off or a buy would exceed the exposure limits
+    /// See docs: https://api.mercadobitcoin.net/api/v4/docs#tag/Trading
+    pub async fn place_order(
+        &self,
+        account_id: &str,
+        symbol: &str,
+        order: &OrderRequest,
+    ) -> Result<PlacedOrder, Error> {
+        self.post(
+            &format!("/accounts/{}/{}/orders", account_id, symbol),
+            &order.body()?,
+        )
+        .await
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OrderStatus {
+    Created,
+    Working,
+    Cancelled,
+    Filled,
+    #[serde(other)]
+    Unknown,
+}
+
+impl OrderStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Created => "created",
+            Self::Working => "working",
+            Self::Cancelled => "cancelled",
+            Self::Filled => "filled",
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Order {
+    pub id: String,
+    pub instrument: String,
+    pub side: Side,
+    #[serde(rename = "type")]
+    pub kind: OrderKind,
+    pub status: OrderStatus,
+    #[serde(deserialize_with = "from_str")]
+    pub qty: f64,
+    #[serde(default, deserialize_with = "from_str")]
+    pub filled_qty: f64,
+    pub limit_price: Option<f64>,
+    pub stop_price: Option<f64>,
+    pub avg_price: Option<f64>,
+    #[serde(default, deserialize_with = "from_str")]
+    pub fee: f64,
+    pub external_id: Option<String>,
+    #[serde(rename = "created_at", with = "ts_seconds")]
+    pub created_at: DateTime<Utc>,
+    #[serde(rename = "updated_at", with = "ts_seconds")]
+    pub updated_at: DateTime<Utc>,
+    /// Only returned when listing with `has_executions`
+    #[serde(default)]
+    pub executions: Vec<Execution>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Liquidity {
+    Maker,
+    Taker,
+}
+
+/// A single fill of an order
+#[derive(Deserialize, Debug, Clone)]
+pub struct Execution {
+    pub id: String,
+    /// e.g. `BTC-BRL`
+    pub instrument: String,
+    pub side: Side,
+    pub liquidity: Liquidity,
+    pub price: f64,
+    #[serde(deserialize_with = "from_str")]
+    pub qty: f64,
+    #[serde(deserialize_with = "from_str")]
+    pub fee_rate: f64,
+    #[serde(with = "ts_seconds")]
+    pub executed_at: DateTime<Utc>,
+}
+
+impl Execution {
+    /// Fee charged for the fill, in the quote asset
+    pub fn fee(&self) -> f64 {
+        self.price * self.qty * self.fee_rate
+    }
+}
+
+/// Optional filters for `Client::list_orders`
+#[derive(Debug, Clone, Default)]
+pub struct ListOrdersFilter {
+    pub side: Option<Side>,
+    pub status: Option<OrderStatus>,
+    pub has_executions: Option<bool>,
+    pub id_from: Option<String>,
+    pub id_to: Option<String>,
+    pub created_at_from: Option<DateTime<Utc>>,
+    pub created_at_to: Option<DateTime<Utc>>,
+}
+
+impl ListOrdersFilter {
+    fn to_query(&self) -> Vec<(&'static str, String)> {
+        let mut query = Vec::new();
+
+        if let Some(side) = self.side {
+            let side = match side {
+                Side::Buy => "buy",
+                Side::Sell => "sell",
+            };
+            query.push(("side", side.to_string()));
+        }
+        if let Some(status) = self.status {
+            query.push(("status", status.as_str().to_string()));
+        }
+        if let Some(has_executions) = self.has_executions {
+            query.push(("has_executions", has_executions.to_string()));
+        }
+        if let Some(id_from) = &self.id_from {
+            query.push(("id_from", id_from.clone()));
+        }
+        if let Some(id_to) = &self.id_to {
+            query.push(("id_to", id_to.clone()));
+        }
+        if let Some(created_at_from) = self.created_at_from {
+            query.push(("created_at_from", created_at_from.timestamp().to_string()));
+        }
+        if let Some(created_at_to) = self.created_at_to {
+            query.push(("created_at_to", created_at_to.timestamp().to_string()));
+        }
+
+        query
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelledOrder {
+    pub order_id: String,
+    pub status: String,
+}
+
+impl Client {
+    /// List the orders of an account in a symbol
+    /// See docs: https://api.mercadobitcoin.net/api/v4/docs#tag/Trading
+    pub async fn list_orders(
+        &self,
+        account_id: &str,
+        symbol: &str,
+        filter: &ListOrdersFilter,
+    ) -> Result<Vec<Order>, Error> {
+        let path = format!("/accounts/{}/{}/orders", account_id, symbol);
+        self.get(&path, &filter.to_query()).await
+    }
+
+    /// Fills of the orders matching the filter, newest order first
+    /// See docs: https://api.mercadobitcoin.net/api/v4/docs#tag/Trading
+    pub async fn list_executions(
+        &self,
+        account_id: &str,
+        symbol: &str,
+        filter: &ListOrdersFilter,
+    ) -> Result<Vec<Execution>, Error> {
+        let filter = ListOrdersFilter {
+            has_executions: Some(true),
+            ..filter.clone()
+        };
+        let orders = self.list_orders(account_id, symbol, &filter).await?;
+
+        Ok(orders
+            .into_iter()
+            .flat_map(|order| order.executions)
+            .collect())
+    }
+
+    /// See docs: https://api.mercadobitcoin.net/api/v4/docs#tag/Trading
+    pub async fn get_order(
+        &self,
+        account_id: &str,
+        symbol: &str,
+        order_id: &str,
+    ) -> Result<Order, Error> {
+        let path = format!("/accounts/{}/{}/orders/{}", account_id, symbol, order_id);
+        self.get(&path, &[]).await
+    }
+
+    /// See docs: https://api.mercadobitcoin.net/api/v4/docs#tag/Trading
+    pub async fn cancel_order(
+        &self,
+        account_id: &str,
+        symbol: &str,
+        order_id: &str,
+    ) -> Result<CancelledOrder, Error> {
+        let path = format!("/accounts/{}/{}/orders/{}", account_id, symbol, order_id);
+        let response: CancelledOrder = self.delete(&path, &[]).await?;
+
+        Ok(CancelledOrder {
+            order_id: order_id.to_string(),
+            ..response
+        })
+    }
+
+    /// Cancel every open order of the account, or only those in `symbol`
+    /// See docs: https://api.mercadobitcoin.net/api/v4/docs#tag/Trading
+    pub async fn cancel_all_orders(
+        &self,
+        account_id: &str,
+        symbol: Option<&str>,
+    ) -> Result<Vec<CancelledOrder>, Error> {
+        let path = format!("/accounts/{}/cancel_all_open_orders", account_id);
+        let query: Vec<_> = symbol
+            .map(|s| ("symbol", s.to_string()))
+            .into_iter()
+            .collect();
+        self.delete(&path, &query).await
+    }
+}
+
+impl Client {
+    /// Stream every order matching the filter, newest first, fetching the
+    /// next page with `id_to` once the current one is consumed
+    pub fn orders(
+        &self,
+        account_id: &str,
+        symbol: &str,
+        filter: ListOrdersFilter,
+    ) -> Paginated<'_, Order> {
+        let account_id = account_id.to_string();
+        let symbol = symbol.to_string();
+
+        paginate(filter, move |mut filter| {
+            let account_id = account_id.clone();
+            let symbol = symbol.clone();
+            async move {
+                let mut orders = self.list_orders(&account_id, &symbol, &filter).await?;
+
+                // `id_to` may be inclusive, skip the order the cursor points at
+                if let Some(id_to) = &filter.id_to {
+                    orders.retain(|order| &order.id != id_to);
+                }
+
+                // Pages are newest first, so the last order is the oldest
+                let next = orders.last().map(|oldest| {
+                    filter.id_to = Some(oldest.id.clone());
+                    filter
+                });
+                Ok((orders, next))
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn amounts_are_sent_as_decimal_strings() {
+        let request = OrderRequest::stop_limit(Side::Buy, 1.005, 250000.125, 1e-7 + 249000.0);
+        let body = serde_json::to_value(request.body().unwrap()).unwrap();
+
+        assert_eq!(body["qty"], "1.00500000");
+        assert_eq!(body["limitPrice"], "250000.13");
+        assert_eq!(body["stopPrice"], "249000.00");
+
+        let body =
+            serde_json::to_value(OrderRequest::market_cost(Side::Buy, 1.005).body().unwrap())
+                .unwrap();
+        assert_eq!(body["cost"], "1.01");
+        assert!(body.get("qty").is_none());
+    }
+
+    #[test]
+    fn non_finite_amounts_are_rejected() {
+        assert!(OrderRequest::market(Side::Sell, f64::NAN).body().is_err());
+        assert!(OrderRequest::market_cost(Side::Buy, f64::INFINITY)
+            .body()
+            .is_err());
+        assert!(OrderRequest::limit(Side::Buy, 1.0, f64::NEG_INFINITY)
+            .body()
+            .is_err());
+    }
+}
@@ -0,0 +1,205 @@
+use super::pagination::{next_page, paginate, Paginated};
+use super::Client;
+use crate::client::{from_str, Error, ParamFormat};
+use chrono::serde::ts_seconds;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_repr::Deserialize_repr;
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct DepositAddress {
+    /// Addresses that credit the account, most assets have a single one
+    pub hashes: Vec<String>,
+    #[serde(default)]
+    pub extra: DepositExtra,
+    pub qrcode: Option<QrCode>,
+}
+
+/// Extra fields some networks require along with the address
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct DepositExtra {
+    /// Destination tag, e.g. for XRP
+    pub address_tag: Option<String>,
+    /// Memo, e.g. for XLM or EOS
+    pub memo: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct QrCode {
+    pub base64: String,
+    /// e.g. `png`
+    pub format: String,
+}
+
+impl DepositAddress {
+    /// The main address, `None` if the exchange didn't return any
+    pub fn address(&self) -> Option<&str> {
+        self.hashes.first().map(String::as_str)
+    }
+}
+
+impl Client {
+    /// Deposit address of an asset in the account. `network` selects the
+    /// chain for assets available in more than one, e.g. `ethereum`.
+    /// See docs: https://api.mercadobitcoin.net/api/v4/docs#tag/Wallet
+    pub async fn deposit_address(
+        &self,
+        account_id: &str,
+        symbol: &str,
+        network: Option<&str>,
+    ) -> Result<DepositAddress, Error> {
+        let path = format!(
+            "/accounts/{}/wallet/{}/deposits/addresses",
+            account_id, symbol
+        );
+        let query: Vec<_> = network
+            .map(|n| ("network", n.to_string()))
+            .into_iter()
+            .collect();
+        self.get(&path, &query).await
+    }
+}
+
+#[derive(Deserialize_repr, Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum WithdrawalStatus {
+    Open = 1,
+    Done = 2,
+    Cancelled = 3,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Withdrawal {
+    pub id: i64,
+    pub coin: String,
+    pub status: WithdrawalStatus,
+    #[serde(deserialize_with = "from_str")]
+    pub quantity: f64,
+    #[serde(deserialize_with = "from_str")]
+    pub fee: f64,
+    /// What reaches the destination, `quantity - fee`
+    #[serde(deserialize_with = "from_str")]
+    pub net_quantity: f64,
+    /// Destination of crypto withdrawals, `None` for BRL
+    pub address: Option<String>,
+    pub destination_tag: Option<String>,
+    pub network: Option<String>,
+    /// Transaction id, once broadcast
+    pub tx: Option<String>,
+    #[serde(with = "ts_seconds")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "ts_seconds")]
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Public: A crypto withdrawal to create with `Client::withdraw`
+#[derive(Serialize, Debug, Clone)]
+pub struct WithdrawalRequest {
+    address: String,
+    quantity: String,
+    tx_fee: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    network: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    destination_tag: Option<String>,
+}
+
+impl WithdrawalRequest {
+    /// Withdraw `quantity` to `address`, paying `fee` as quoted by
+    /// `Client::asset_fees`. Both are written with 8 decimals, failing with
+    /// `Error::InvalidOrderParam` for values that can't be.
+    pub fn new(address: &str, quantity: f64, fee: f64) -> Result<Self, Error> {
+        let format = |value| ParamFormat::SmallestUnit.format(value, 8);
+
+        Ok(WithdrawalRequest {
+            address: address.to_string(),
+            quantity: format(quantity)?,
+            tx_fee: format(fee)?,
+            network: None,
+            destination_tag: None,
+        })
+    }
+
+    /// Chain to withdraw through, for assets available in more than one
+    pub fn with_network(mut self, network: &str) -> Self {
+        self.network = Some(network.to_string());
+        self
+    }
+
+    pub fn with_destination_tag(mut self, tag: &str) -> Self {
+        self.destination_tag = Some(tag.to_string());
+        self
+    }
+}
+
+impl Client {
+    /// Withdrawals of an asset, newest first. `page` starts at 1.
+    /// See docs: https://api.mercadobitcoin.net/api/v4/docs#tag/Wallet
+    pub async fn list_withdrawals(
+        &self,
+        account_id: &str,
+        symbol: &str,
+        page: u32,
+        page_size: u32,
+    ) -> Result<Vec<Withdrawal>, Error> {
+        let path = format!("/accounts/{}/wallet/{}/withdraw", account_id, symbol);
+        let query = [
+            ("page", page.to_string()),
+            ("page_size", page_size.to_string()),
+        ];
+        self.get(&path, &query).await
+    }
+
+    /// See docs: https://api.mercadobitcoin.net/api/v4/docs#tag/Wallet
+    pub async fn get_withdrawal(
+        &self,
+        account_id: &str,
+        symbol: &str,
+        withdrawal_id: i64,
+    ) -> Result<Withdrawal, Error> {
+        let path = format!(
+            "/accounts/{}/wallet/{}/withdraw/{}",
+            account_id, symbol, withdrawal_id
+        );
+        self.get(&path, &[]).await
+    }
+
+    /// Create a crypto withdrawal. It starts `Open`, poll it with
+    /// `get_withdrawal` to follow it.
+    /// See docs: https://api.mercadobitcoin.net/api/v4/docs#tag/Wallet
+    pub async fn withdraw(
+        &self,
+        account_id: &str,
+        symbol: &str,
+        request: &WithdrawalRequest,
+    ) -> Result<Withdrawal, Error> {
+        let path = format!("/accounts/{}/wallet/{}/withdraw", account_id, symbol);
+        self.post(&path, request).await
+    }
+}
+
+impl Client {
+    /// Stream every withdrawal of an asset, newest first, `page_size` at a
+    /// time
+    pub fn withdrawals(
+        &self,
+        account_id: &str,
+        symbol: &str,
+        page_size: u32,
+    ) -> Paginated<'_, Withdrawal> {
+        let account_id = account_id.to_string();
+        let symbol = symbol.to_string();
+
+        paginate(1, move |page| {
+            let account_id = account_id.clone();
+            let symbol = symbol.clone();
+            async move {
+                let withdrawals = self
+                    .list_withdrawals(&account_id, &symbol, page, page_size)
+                    .await?;
+                let next = next_page(page, withdrawals.len(), page_size);
+                Ok((withdrawals, next))
+            }
+        })
+    }
+}
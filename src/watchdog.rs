@@ -0,0 +1,174 @@
+use crate::client::{ApiStatus, Client, Error};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::time::{sleep, sleep_until, Instant};
+
+/// Backoff between attempts to cancel the open orders once the switch fires
+const INITIAL_CANCEL_BACKOFF: Duration = Duration::from_millis(200);
+const MAX_CANCEL_BACKOFF: Duration = Duration::from_secs(5);
+/// Attempts to cancel the open orders of a pair before giving up on it
+const MAX_CANCEL_ATTEMPTS: u32 = 10;
+
+/// Coin pairs whose orders couldn't be cancelled, with the last error
+type Failures = Arc<Mutex<Vec<(String, Arc<Error>)>>>;
+
+/// Public: Cancels every open order of some coin pairs unless it's refreshed
+/// in time, see `Client::cancel_all_after`.
+///
+/// Dropping the handle doesn't stop it: an application that stops
+/// refreshing, for whatever reason, gets its orders cancelled. Call
+/// `disarm` to stop it on purpose.
+#[derive(Debug)]
+pub struct DeadMansSwitch {
+    timeout: Duration,
+    deadline: watch::Sender<Option<Instant>>,
+    fired: Arc<AtomicBool>,
+    failures: Failures,
+}
+
+impl DeadMansSwitch {
+    /// Push the deadline `timeout` from now
+    pub fn refresh(&self) {
+        let _ = self.deadline.send(Some(Instant::now() + self.timeout));
+    }
+
+    pub fn disarm(self) {
+        let _ = self.deadline.send(None);
+    }
+
+    /// Whether the deadline passed and the orders are being (or were)
+    /// cancelled
+    pub fn fired(&self) -> bool {
+        self.fired.load(Ordering::SeqCst)
+    }
+
+    /// Coin pairs given up on after the switch fired, with the error of the
+    /// last attempt: the credentials or the endpoint were refused, or the
+    /// attempts ran out. Their orders may still be open.
+    pub fn failures(&self) -> Vec<(String, Arc<Error>)> {
+        self.failures.lock().unwrap().clone()
+    }
+}
+
+/// Whether cancelling again can't succeed without a change on our side
+fn is_permanent(error: &Error) -> bool {
+    match error {
+        Error::ApiError(status) => matches!(
+            status,
+            ApiStatus::PostRequestRequired
+                | ApiStatus::InvalidTapiID
+                | ApiStatus::InvalidTapiMac
+                | ApiStatus::InvalidTapiMethod
+                | ApiStatus::InvalidCoinPair
+                | ApiStatus::ReadOnlyKey
+                | ApiStatus::RequestBlocked
+        ),
+        Error::ClientNotConfigured(_) | Error::SignerError(_) | Error::EndpointNotAllowed(_) => {
+            true
+        }
+        _ => false,
+    }
+}
+
+impl Client {
+    /// Cancel every open order of `coin_pairs` if the returned switch isn't
+    /// refreshed within `timeout`, protecting against the application
+    /// wedging while its orders rest in the book.
+    ///
+    /// The API has no native support for it, so the countdown runs in a task
+    /// spawned on the current tokio runtime. Once it fires, cancellation is
+    /// retried with backoff until every open order is cancelled, giving up
+    /// on a pair after 10 attempts or on errors that retrying can't fix,
+    /// see `DeadMansSwitch::failures`.
+    pub fn cancel_all_after(&self, coin_pairs: &[&str], timeout: Duration) -> DeadMansSwitch {
+        let (sender, mut receiver) = watch::channel(Some(Instant::now() + timeout));
+        let fired = Arc::new(AtomicBool::new(false));
+        let failures = Failures::default();
+
+        let client = self.clone();
+        let coin_pairs: Vec<String> = coin_pairs.iter().map(|p| p.to_string()).collect();
+        let task_fired = Arc::clone(&fired);
+        let task_failures = Arc::clone(&failures);
+
+        tokio::spawn(async move {
+            // Sleep until the deadline and check whether it moved meanwhile
+            loop {
+                let deadline = match *receiver.borrow_and_update() {
+                    Some(deadline) => deadline,
+                    None => return,
+                };
+                sleep_until(deadline).await;
+
+                if *receiver.borrow() == Some(deadline) {
+                    break;
+                }
+            }
+
+            task_fired.store(true, Ordering::SeqCst);
+            client.cancel_until_done(coin_pairs, &task_failures).await;
+        });
+
+        DeadMansSwitch {
+            timeout,
+            deadline: sender,
+            fired,
+            failures,
+        }
+    }
+
+    async fn cancel_until_done(&self, mut coin_pairs: Vec<String>, failures: &Failures) {
+        let mut backoff = INITIAL_CANCEL_BACKOFF;
+
+        for attempt in 1..=MAX_CANCEL_ATTEMPTS {
+            let mut pending = Vec::new();
+
+            for coin_pair in coin_pairs {
+                let error = match self.cancel_all_open_orders(coin_pair.clone()).await {
+                    Ok(reports) => reports
+                        .into_iter()
+                        .filter_map(|report| report.result.err())
+                        .last(),
+                    Err(e) => Some(e),
+                };
+                let error = match error {
+                    Some(error) => error,
+                    None => continue,
+                };
+
+                if is_permanent(&error) || attempt == MAX_CANCEL_ATTEMPTS {
+                    failures.lock().unwrap().push((coin_pair, Arc::new(error)));
+                } else {
+                    pending.push(coin_pair);
+                }
+            }
+
+            coin_pairs = pending;
+            if coin_pairs.is_empty() {
+                return;
+            }
+            sleep(backoff).await;
+            backoff = (backoff * 2).min(MAX_CANCEL_BACKOFF);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn permanent_errors_are_reported_without_retrying() {
+        let client = Client::default();
+        let switch = client.cancel_all_after(&["BRLBTC"], Duration::from_millis(10));
+
+        sleep(Duration::from_millis(100)).await;
+
+        assert!(switch.fired());
+        let failures = switch.failures();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].0, "BRLBTC");
+        assert!(matches!(*failures[0].1, Error::ClientNotConfigured(_)));
+    }
+}
@@ -0,0 +1,323 @@
+use super::{Event, Orderbook, PriceLevel};
+use crate::client::Error;
+use crate::v4;
+use futures::future::{self, Either};
+use futures::{Stream, StreamExt};
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
+use std::future::Future;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BookSide {
+    Bid,
+    Ask,
+}
+
+/// Public: An orderbook kept up to date from websocket messages, sorted by
+/// price. The feed pushes timestamped snapshots of the top levels rather
+/// than diffs, so each one replaces the book unless it's older.
+#[derive(Debug, Clone)]
+pub struct LiveOrderbook {
+    coin_pair: String,
+    bids: BTreeMap<Decimal, Decimal>,
+    asks: BTreeMap<Decimal, Decimal>,
+    timestamp: Option<i64>,
+    needs_snapshot: bool,
+}
+
+impl LiveOrderbook {
+    /// Public: An empty book for `coin_pair`, e.g. `BRLBTC`, waiting for a
+    /// snapshot
+    pub fn new(coin_pair: &str) -> Self {
+        LiveOrderbook {
+            coin_pair: coin_pair.to_uppercase(),
+            bids: BTreeMap::new(),
+            asks: BTreeMap::new(),
+            timestamp: None,
+            needs_snapshot: true,
+        }
+    }
+
+    pub fn coin_pair(&self) -> &str {
+        &self.coin_pair
+    }
+
+    /// Timestamp of the snapshot the book was last loaded from
+    pub fn timestamp(&self) -> Option<i64> {
+        self.timestamp
+    }
+
+    /// Replace the whole book
+    pub fn apply_snapshot(&mut self, orderbook: &Orderbook) {
+        let levels = |levels: &[PriceLevel]| {
+            levels
+                .iter()
+                .filter(|level| !level.quantity.is_zero())
+                .map(|level| (level.price, level.quantity))
+                .collect()
+        };
+
+        self.bids = levels(&orderbook.bids);
+        self.asks = levels(&orderbook.asks);
+        self.timestamp = Some(orderbook.timestamp);
+        self.needs_snapshot = false;
+    }
+
+    /// Apply an orderbook event of this book's pair, ignoring snapshots
+    /// older than the book and every other event but `Event::Reconnected`,
+    /// which leaves the book waiting for a snapshot. Returns whether the
+    /// book changed.
+    pub fn apply_event(&mut self, event: &Event) -> bool {
+        match event {
+            Event::Orderbook {
+                coin_pair,
+                orderbook,
+            } if *coin_pair == self.coin_pair => {
+                if !self.needs_snapshot
+                    && self
+                        .timestamp
+                        .is_some_and(|timestamp| orderbook.timestamp < timestamp)
+                {
+                    return false;
+                }
+                self.apply_snapshot(orderbook);
+                true
+            }
+            Event::Reconnected { .. } => {
+                self.needs_snapshot = true;
+                false
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether the book has no snapshot yet, or the connection dropped since
+    /// the last one and it may be outdated
+    pub fn needs_snapshot(&self) -> bool {
+        self.needs_snapshot
+    }
+
+    pub fn best_bid(&self) -> Option<PriceLevel> {
+        self.bids.iter().next_back().map(level)
+    }
+
+    pub fn best_ask(&self) -> Option<PriceLevel> {
+        self.asks.iter().next().map(level)
+    }
+
+    pub fn spread(&self) -> Option<Decimal> {
+        Some(self.best_ask()?.price - self.best_bid()?.price)
+    }
+
+    pub fn mid(&self) -> Option<Decimal> {
+        Some((self.best_ask()?.price + self.best_bid()?.price) / Decimal::from(2))
+    }
+
+    /// The best `levels` bids, highest first
+    pub fn bids(&self, levels: usize) -> Vec<PriceLevel> {
+        self.bids.iter().rev().take(levels).map(level).collect()
+    }
+
+    /// The best `levels` asks, lowest first
+    pub fn asks(&self, levels: usize) -> Vec<PriceLevel> {
+        self.asks.iter().take(levels).map(level).collect()
+    }
+
+    /// Quantity resting at exactly `price` on a side
+    pub fn quantity_at(&self, side: BookSide, price: Decimal) -> Decimal {
+        let levels = match side {
+            BookSide::Bid => &self.bids,
+            BookSide::Ask => &self.asks,
+        };
+        levels.get(&price).copied().unwrap_or_default()
+    }
+
+    /// Total quantity offered at `price` or better on a side: bids at or
+    /// above it, asks at or below it
+    pub fn depth_to(&self, side: BookSide, price: Decimal) -> Decimal {
+        match side {
+            BookSide::Bid => self.bids.range(price..).map(|(_, q)| *q).sum(),
+            BookSide::Ask => self.asks.range(..=price).map(|(_, q)| *q).sum(),
+        }
+    }
+}
+
+fn level((price, quantity): (&Decimal, &Decimal)) -> PriceLevel {
+    PriceLevel {
+        price: *price,
+        quantity: *quantity,
+    }
+}
+
+impl From<&v4::Orderbook> for Orderbook {
+    fn from(orderbook: &v4::Orderbook) -> Self {
+        let levels = |levels: &[v4::PriceLevel]| {
+            levels
+                .iter()
+                .map(|level| PriceLevel {
+                    price: level.price,
+                    quantity: level.quantity,
+                })
+                .collect()
+        };
+
+        Orderbook {
+            timestamp: orderbook.timestamp,
+            asks: levels(&orderbook.asks),
+            bids: levels(&orderbook.bids),
+        }
+    }
+}
+
+/// Build a `LiveOrderbook` from a REST snapshot, e.g. the v4 orderbook
+/// converted with `Orderbook::from`, and the websocket events of its pair,
+/// e.g. from `Connection::into_stream`.
+///
+/// Events arriving while the snapshot is fetched are buffered. Once it's
+/// in, the book is loaded from it and the buffered events applied in order,
+/// so book messages older than the snapshot are dropped and newer ones
+/// replace it. `events` must be subscribed to the pair's orderbook before
+/// `snapshot` is requested. Keep applying `events` to the returned book
+/// with `apply_event`.
+pub async fn bootstrap_orderbook<S, F>(
+    coin_pair: &str,
+    events: &mut S,
+    snapshot: F,
+) -> Result<LiveOrderbook, Error>
+where
+    S: Stream<Item = Result<Event, Error>> + Unpin,
+    F: Future<Output = Result<Orderbook, Error>>,
+{
+    let mut buffered = Vec::new();
+    let mut snapshot = Box::pin(snapshot);
+
+    let snapshot = loop {
+        match future::select(snapshot, events.next()).await {
+            Either::Left((snapshot, _)) => break snapshot?,
+            Either::Right((Some(event), pending)) => {
+                buffered.push(event?);
+                snapshot = pending;
+            }
+            Either::Right((None, pending)) => break pending.await?,
+        }
+    };
+
+    let mut book = LiveOrderbook::new(coin_pair);
+    book.apply_snapshot(&snapshot);
+    for event in &buffered {
+        book.apply_event(event);
+    }
+
+    Ok(book)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::stream;
+    use std::time::Duration;
+
+    fn level(price: i64, quantity: i64) -> PriceLevel {
+        PriceLevel {
+            price: Decimal::from(price),
+            quantity: Decimal::from(quantity),
+        }
+    }
+
+    fn orderbook(timestamp: i64, bid: i64, ask: i64) -> Orderbook {
+        Orderbook {
+            timestamp,
+            asks: vec![level(ask, 1), level(ask + 10, 2)],
+            bids: vec![level(bid, 3), level(bid - 10, 0)],
+        }
+    }
+
+    fn book_event(coin_pair: &str, orderbook: Orderbook) -> Event {
+        Event::Orderbook {
+            coin_pair: coin_pair.to_string(),
+            orderbook,
+        }
+    }
+
+    #[test]
+    fn snapshot_replaces_the_book() {
+        let mut book = LiveOrderbook::new("brlbtc");
+        assert!(book.needs_snapshot());
+
+        book.apply_snapshot(&orderbook(100, 90, 110));
+
+        assert!(!book.needs_snapshot());
+        assert_eq!(book.timestamp(), Some(100));
+        assert_eq!(book.best_bid(), Some(level(90, 3)));
+        assert_eq!(book.best_ask(), Some(level(110, 1)));
+        assert_eq!(book.bids(10), vec![level(90, 3)]);
+        assert_eq!(book.asks(10), vec![level(110, 1), level(120, 2)]);
+        assert_eq!(book.spread(), Some(Decimal::from(20)));
+        assert_eq!(
+            book.depth_to(BookSide::Ask, Decimal::from(120)),
+            Decimal::from(3)
+        );
+    }
+
+    #[test]
+    fn events_update_the_book_unless_older() {
+        let mut book = LiveOrderbook::new("BRLBTC");
+        book.apply_snapshot(&orderbook(100, 90, 110));
+
+        assert!(book.apply_event(&book_event("BRLBTC", orderbook(101, 95, 105))));
+        assert_eq!(book.best_bid(), Some(level(95, 3)));
+
+        assert!(!book.apply_event(&book_event("BRLBTC", orderbook(99, 80, 120))));
+        assert!(!book.apply_event(&book_event("BRLETH", orderbook(102, 80, 120))));
+        assert_eq!(book.timestamp(), Some(101));
+        assert_eq!(book.best_ask(), Some(level(105, 1)));
+    }
+
+    #[test]
+    fn reconnection_waits_for_a_snapshot() {
+        let mut book = LiveOrderbook::new("BRLBTC");
+        book.apply_snapshot(&orderbook(100, 90, 110));
+
+        assert!(!book.apply_event(&Event::Reconnected { attempts: 1 }));
+        assert!(book.needs_snapshot());
+
+        // Any snapshot is taken after a reconnection, even an older one
+        assert!(book.apply_event(&book_event("BRLBTC", orderbook(50, 70, 130))));
+        assert!(!book.needs_snapshot());
+        assert_eq!(book.timestamp(), Some(50));
+    }
+
+    #[tokio::test]
+    async fn bootstrap_stitches_the_snapshot_with_buffered_events() {
+        let mut events = stream::iter(vec![
+            Ok(book_event("BRLBTC", orderbook(99, 80, 120))),
+            Ok(book_event("BRLETH", orderbook(102, 10, 20))),
+            Ok(book_event("BRLBTC", orderbook(101, 95, 105))),
+        ]);
+        let snapshot = async {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            Ok(orderbook(100, 90, 110))
+        };
+
+        let book = bootstrap_orderbook("BRLBTC", &mut events, snapshot)
+            .await
+            .unwrap();
+
+        assert_eq!(book.timestamp(), Some(101));
+        assert_eq!(book.best_bid(), Some(level(95, 3)));
+        assert_eq!(book.best_ask(), Some(level(105, 1)));
+    }
+
+    #[tokio::test]
+    async fn bootstrap_fails_on_a_feed_error() {
+        let mut events = stream::iter(vec![Err(Error::UnexpectedResponse("closed".into()))]);
+        let snapshot = async {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            Ok(orderbook(100, 90, 110))
+        };
+
+        assert!(bootstrap_orderbook("BRLBTC", &mut events, snapshot)
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,88 @@
+use super::{Connection, Event};
+use std::sync::Arc;
+use tokio::sync::{broadcast, oneshot};
+
+/// Public: Broadcasts every event of one connection to many consumers, e.g.
+/// a quoter and a logger, so they don't each open their own connection.
+///
+/// Subscribe the connection to what the consumers need before handing it
+/// over; each consumer gets all of its events and picks the ones it cares
+/// about. Unlike `SubscriptionManager`, consumers don't affect what's
+/// subscribed. The connection is closed once every clone of the bus is
+/// dropped.
+#[derive(Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<Event>,
+    _shutdown: Arc<oneshot::Sender<()>>,
+}
+
+/// Public: One consumer's view of an `EventBus`
+pub struct EventReceiver {
+    receiver: broadcast::Receiver<Event>,
+}
+
+impl EventBus {
+    /// Public: Take over `connection`, reading it in a task spawned on the
+    /// current tokio runtime. Up to `capacity` events are buffered for
+    /// consumers that fall behind.
+    pub fn spawn(connection: Connection, capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        let (shutdown, stop) = oneshot::channel();
+        tokio::spawn(run(connection, sender.clone(), stop));
+
+        EventBus {
+            sender,
+            _shutdown: Arc::new(shutdown),
+        }
+    }
+
+    /// A new consumer, receiving the events published from now on
+    pub fn receiver(&self) -> EventReceiver {
+        EventReceiver {
+            receiver: self.sender.subscribe(),
+        }
+    }
+
+    /// Number of consumers currently attached
+    pub fn consumers(&self) -> usize {
+        self.sender.receiver_count()
+    }
+}
+
+impl EventReceiver {
+    /// The next event, `None` once the connection is gone. Events missed by
+    /// a consumer that fell too far behind are skipped.
+    pub async fn recv(&mut self) -> Option<Event> {
+        loop {
+            match self.receiver.recv().await {
+                Ok(event) => return Some(event),
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+async fn run(
+    mut connection: Connection,
+    sender: broadcast::Sender<Event>,
+    mut stop: oneshot::Receiver<()>,
+) {
+    loop {
+        tokio::select! {
+            _ = &mut stop => break,
+            event = connection.next_event() => match event {
+                // Nobody listening is fine, consumers may attach later
+                Some(Ok(event)) => {
+                    let _ = sender.send(event);
+                }
+                // Errors reach consumers as a closed bus once the
+                // connection gives up
+                Some(Err(_)) => continue,
+                None => return,
+            },
+        }
+    }
+
+    let _ = connection.close().await;
+}
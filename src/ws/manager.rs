@@ -0,0 +1,191 @@
+use super::{Channel, Connection, Event, Subscription};
+use crate::client::Error;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::{broadcast, mpsc, oneshot};
+
+/// Events buffered per subscription for consumers that fall behind
+const SUBSCRIPTION_BUFFER: usize = 1024;
+
+/// What each subscriber receives: an event, or an error of the connection
+type Item = Result<Event, Arc<Error>>;
+
+enum Request {
+    Subscribe {
+        subscription: Subscription,
+        reply: oneshot::Sender<Result<broadcast::Receiver<Item>, Error>>,
+    },
+    Release {
+        subscription: Subscription,
+    },
+}
+
+struct Active {
+    sender: broadcast::Sender<Item>,
+    consumers: usize,
+}
+
+/// Public: Shares one connection between many consumers.
+///
+/// Each (channel, coin pair) is subscribed once however many consumers ask
+/// for it, and its events are fanned out to all of them. It's unsubscribed
+/// when the last `Subscriber` is dropped. `Reconnected` and `StaleFeed`
+/// events, and errors of the connection, reach every consumer.
+#[derive(Clone)]
+pub struct SubscriptionManager {
+    requests: mpsc::UnboundedSender<Request>,
+}
+
+/// Public: Events of one subscription, unsubscribing on drop
+pub struct Subscriber {
+    subscription: Subscription,
+    receiver: broadcast::Receiver<Item>,
+    requests: mpsc::UnboundedSender<Request>,
+}
+
+impl Subscriber {
+    pub fn subscription(&self) -> &Subscription {
+        &self.subscription
+    }
+
+    /// The next event or connection error, `None` once the connection is
+    /// gone. Events missed by a consumer that fell too far behind are
+    /// skipped.
+    pub async fn recv(&mut self) -> Option<Result<Event, Arc<Error>>> {
+        loop {
+            match self.receiver.recv().await {
+                Ok(event) => return Some(event),
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+impl Drop for Subscriber {
+    fn drop(&mut self) {
+        let _ = self.requests.send(Request::Release {
+            subscription: self.subscription.clone(),
+        });
+    }
+}
+
+impl SubscriptionManager {
+    /// Public: Take over `connection`, reading it in a task spawned on the
+    /// current tokio runtime until every manager and subscriber is dropped
+    pub fn spawn(connection: Connection) -> Self {
+        let (requests, receiver) = mpsc::unbounded_channel();
+        tokio::spawn(run(connection, receiver));
+        SubscriptionManager { requests }
+    }
+
+    pub async fn subscribe(&self, subscription: Subscription) -> Result<Subscriber, Error> {
+        let (reply, response) = oneshot::channel();
+        let closed = || Error::UnexpectedResponse("websocket connection closed".to_string());
+
+        self.requests
+            .send(Request::Subscribe {
+                subscription: subscription.clone(),
+                reply,
+            })
+            .map_err(|_| closed())?;
+        let receiver = response.await.map_err(|_| closed())??;
+
+        Ok(Subscriber {
+            subscription,
+            receiver,
+            requests: self.requests.clone(),
+        })
+    }
+}
+
+/// The channel an event belongs to, `None` for connection events
+fn channel_of(event: &Event) -> Option<Channel> {
+    match event {
+        Event::Ticker { .. } => Some(Channel::Ticker),
+        Event::Orderbook { .. } => Some(Channel::Orderbook),
+        Event::Trade { .. } => Some(Channel::Trade),
+        _ => None,
+    }
+}
+
+async fn run(mut connection: Connection, mut requests: mpsc::UnboundedReceiver<Request>) {
+    let mut active: HashMap<Subscription, Active> = HashMap::new();
+
+    loop {
+        tokio::select! {
+            request = requests.recv() => match request {
+                Some(request) => handle(&mut connection, &mut active, request).await,
+                None => break,
+            },
+            // `next_event` is cancel safe, so a request arriving mid
+            // reconnection doesn't lose the replayed subscriptions
+            event = connection.next_event() => match event {
+                Some(event) => dispatch(&active, event.map_err(Arc::new)),
+                None => break,
+            },
+        }
+    }
+}
+
+async fn handle(
+    connection: &mut Connection,
+    active: &mut HashMap<Subscription, Active>,
+    request: Request,
+) {
+    match request {
+        Request::Subscribe {
+            subscription,
+            reply,
+        } => {
+            if let Some(entry) = active.get_mut(&subscription) {
+                entry.consumers += 1;
+                let _ = reply.send(Ok(entry.sender.subscribe()));
+                return;
+            }
+
+            let result = connection.subscribe(&subscription).await.map(|()| {
+                let (sender, receiver) = broadcast::channel(SUBSCRIPTION_BUFFER);
+                active.insert(
+                    subscription,
+                    Active {
+                        sender,
+                        consumers: 1,
+                    },
+                );
+                receiver
+            });
+            let _ = reply.send(result);
+        }
+        Request::Release { subscription } => {
+            let last = match active.get_mut(&subscription) {
+                Some(entry) => {
+                    entry.consumers -= 1;
+                    entry.consumers == 0
+                }
+                None => false,
+            };
+            if last {
+                active.remove(&subscription);
+                let _ = connection.unsubscribe(&subscription).await;
+            }
+        }
+    }
+}
+
+fn dispatch(active: &HashMap<Subscription, Active>, item: Item) {
+    for (subscription, entry) in active {
+        let matches = match &item {
+            Ok(event) => match channel_of(event) {
+                Some(channel) => {
+                    subscription.name == channel && event.coin_pair() == Some(&subscription.id)
+                }
+                None => true,
+            },
+            Err(_) => true,
+        };
+        if matches {
+            let _ = entry.sender.send(item.clone());
+        }
+    }
+}
@@ -0,0 +1,871 @@
+//! Client for the Mercado Bitcoin websocket feed, which pushes tickers,
+//! orderbooks and trades as they change instead of having them polled.
+//! See docs: https://ws.mercadobitcoin.net/docs/v0/
+
+mod book;
+mod bus;
+mod manager;
+
+pub use book::{bootstrap_orderbook, BookSide, LiveOrderbook};
+pub use bus::{EventBus, EventReceiver};
+pub use manager::{Subscriber, SubscriptionManager};
+
+use crate::client::{Error, RetryPolicy, TradeType};
+use chrono::serde::ts_seconds;
+use chrono::{DateTime, Utc};
+use futures::{stream, SinkExt, Stream, StreamExt};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::time::{timeout_at, Instant};
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+pub const WS_URL: &str = "wss://ws.mercadobitcoin.net/ws";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Channel {
+    Ticker,
+    Orderbook,
+    Trade,
+}
+
+/// Public: A channel of one market, e.g. the `BRLBTC` orderbook
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Subscription {
+    pub name: Channel,
+    /// Coin pair, e.g. `BRLBTC`
+    pub id: String,
+    /// Depth of orderbook subscriptions: 10, 20, 50, 100 or 200 levels
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+}
+
+impl Subscription {
+    pub fn ticker(coin_pair: &str) -> Self {
+        Self::new(Channel::Ticker, coin_pair)
+    }
+
+    pub fn orderbook(coin_pair: &str, limit: u32) -> Self {
+        Subscription {
+            limit: Some(limit),
+            ..Self::new(Channel::Orderbook, coin_pair)
+        }
+    }
+
+    pub fn trades(coin_pair: &str) -> Self {
+        Self::new(Channel::Trade, coin_pair)
+    }
+
+    fn new(name: Channel, coin_pair: &str) -> Self {
+        Subscription {
+            name,
+            id: coin_pair.to_uppercase(),
+            limit: None,
+        }
+    }
+}
+
+/// Messages sent to the server
+#[derive(Serialize, Debug)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum Command<'a> {
+    Subscribe { subscription: &'a Subscription },
+    Unsubscribe { subscription: &'a Subscription },
+    Ping,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Ticker {
+    pub high: Decimal,
+    pub low: Decimal,
+    pub vol: Decimal,
+    pub last: Decimal,
+    pub buy: Decimal,
+    pub sell: Decimal,
+    pub open: Decimal,
+    #[serde(with = "ts_seconds")]
+    pub date: DateTime<Utc>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(from = "(Decimal, Decimal)")]
+pub struct PriceLevel {
+    pub price: Decimal,
+    pub quantity: Decimal,
+}
+
+impl From<(Decimal, Decimal)> for PriceLevel {
+    fn from((price, quantity): (Decimal, Decimal)) -> Self {
+        PriceLevel { price, quantity }
+    }
+}
+
+/// The top levels of a book, each message replaces the previous one
+#[derive(Deserialize, Debug, Clone)]
+pub struct Orderbook {
+    pub timestamp: i64,
+    /// Best (lowest) price first
+    pub asks: Vec<PriceLevel>,
+    /// Best (highest) price first
+    pub bids: Vec<PriceLevel>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Trade {
+    pub tid: i64,
+    #[serde(with = "ts_seconds")]
+    pub date: DateTime<Utc>,
+    #[serde(rename = "type")]
+    pub trade_type: TradeType,
+    pub price: Decimal,
+    pub amount: Decimal,
+}
+
+/// Public: A message pushed by the server
+#[derive(Debug, Clone)]
+pub enum Event {
+    Ticker {
+        coin_pair: String,
+        ticker: Ticker,
+    },
+    Orderbook {
+        coin_pair: String,
+        orderbook: Orderbook,
+    },
+    Trade {
+        coin_pair: String,
+        trade: Trade,
+    },
+    Pong,
+    /// The connection dropped and was opened again, with every subscription
+    /// replayed. Updates sent meanwhile were missed, so state built from
+    /// them (e.g. a `LiveOrderbook`) must be resynced.
+    Reconnected {
+        attempts: u32,
+    },
+    /// Nothing arrived for longer than `Connection::with_stale_after`, the
+    /// data received so far may be outdated
+    StaleFeed {
+        silent_for: Duration,
+    },
+    /// The server rejected a command, e.g. an unknown coin pair
+    Error {
+        message: String,
+    },
+}
+
+/// Public: An item of a single-subscription stream such as
+/// `Client::trade_stream`: data of the subscribed pair, or a change in the
+/// connection delivering it
+#[derive(Debug, Clone)]
+pub enum StreamItem<T> {
+    Data(T),
+    /// See `Event::Reconnected`, data pushed while disconnected was missed
+    Reconnected {
+        attempts: u32,
+    },
+    /// See `Event::StaleFeed`, the last data received may be outdated
+    StaleFeed {
+        silent_for: Duration,
+    },
+}
+
+impl Event {
+    /// The coin pair of market data events
+    pub fn coin_pair(&self) -> Option<&str> {
+        match self {
+            Event::Ticker { coin_pair, .. }
+            | Event::Orderbook { coin_pair, .. }
+            | Event::Trade { coin_pair, .. } => Some(coin_pair),
+            _ => None,
+        }
+    }
+}
+
+impl From<&Ticker> for crate::Ticker {
+    fn from(ticker: &Ticker) -> Self {
+        let to_f32 = |value: Decimal| value.to_f32().unwrap_or_default();
+
+        crate::Ticker {
+            high: to_f32(ticker.high),
+            low: to_f32(ticker.low),
+            vol: to_f32(ticker.vol),
+            last: to_f32(ticker.last),
+            buy: to_f32(ticker.buy),
+            sell: to_f32(ticker.sell),
+            date: ticker.date,
+        }
+    }
+}
+
+/// Envelope of every server message, `data` being decoded by type
+#[derive(Deserialize)]
+struct RawEvent {
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    id: Option<String>,
+    data: Option<serde_json::Value>,
+    message: Option<String>,
+}
+
+impl RawEvent {
+    fn decode(self) -> Result<Option<Event>, serde_json::Error> {
+        let coin_pair = self.id.unwrap_or_default();
+        let data = self.data.unwrap_or_default();
+
+        let event = match self.kind.as_deref() {
+            Some("ticker") => Event::Ticker {
+                coin_pair,
+                ticker: serde_json::from_value(data)?,
+            },
+            Some("orderbook") => Event::Orderbook {
+                coin_pair,
+                orderbook: serde_json::from_value(data)?,
+            },
+            Some("trade") => Event::Trade {
+                coin_pair,
+                trade: serde_json::from_value(data)?,
+            },
+            Some("pong") => Event::Pong,
+            _ => match self.message {
+                Some(message) => Event::Error { message },
+                None => return Ok(None),
+            },
+        };
+
+        Ok(Some(event))
+    }
+}
+
+/// Public: Opens connections to the websocket feed
+#[derive(Debug, Clone)]
+pub struct Client {
+    url: String,
+    reconnect: Option<RetryPolicy>,
+    heartbeat: Option<Duration>,
+    stale_after: Option<Duration>,
+}
+
+impl Default for Client {
+    fn default() -> Self {
+        Client::new(WS_URL.to_string())
+    }
+}
+
+impl Client {
+    /// Public: A client for the feed at `url`, `Client::default()` connects
+    /// to `WS_URL`
+    pub fn new(url: String) -> Self {
+        Client {
+            url,
+            reconnect: None,
+            heartbeat: None,
+            stale_after: None,
+        }
+    }
+
+    /// Public: Reconnect the connections opened by this client when they
+    /// drop, see `Connection::with_reconnect`
+    pub fn with_reconnect(mut self, policy: RetryPolicy) -> Self {
+        self.reconnect = Some(policy);
+        self
+    }
+
+    /// Public: Ping the server on the connections opened by this client,
+    /// see `Connection::with_heartbeat`
+    pub fn with_heartbeat(mut self, interval: Duration) -> Self {
+        self.heartbeat = Some(interval);
+        self
+    }
+
+    /// Public: Detect stale connections, see `Connection::with_stale_after`
+    pub fn with_stale_after(mut self, timeout: Duration) -> Self {
+        self.stale_after = Some(timeout);
+        self
+    }
+
+    pub async fn connect(&self) -> Result<Connection, Error> {
+        let mut connection = Connection::connect(&self.url).await?;
+        connection.reconnect = self.reconnect.clone();
+        connection.heartbeat = self.heartbeat;
+        connection.stale_after = self.stale_after;
+        Ok(connection)
+    }
+
+    /// Open a connection streaming the trades of `coin_pair`, e.g. `BRLBTC`
+    pub async fn trade_stream(
+        &self,
+        coin_pair: &str,
+    ) -> Result<impl Stream<Item = Result<StreamItem<Trade>, Error>>, Error> {
+        self.subscription_stream(Subscription::trades(coin_pair), |event| match event {
+            Event::Trade { trade, .. } => Some(trade),
+            _ => None,
+        })
+        .await
+    }
+
+    /// Open a connection streaming the ticker of `coin_pair`, decoded into
+    /// the same `Ticker` the REST endpoint returns
+    pub async fn ticker_stream(
+        &self,
+        coin_pair: &str,
+    ) -> Result<impl Stream<Item = Result<StreamItem<crate::Ticker>, Error>>, Error> {
+        self.subscription_stream(Subscription::ticker(coin_pair), |event| match event {
+            Event::Ticker { ticker, .. } => Some(crate::Ticker::from(&ticker)),
+            _ => None,
+        })
+        .await
+    }
+
+    /// Subscribe on a new connection and stream what `extract` takes from the
+    /// events of the subscribed pair, along with reconnections and stale feeds
+    async fn subscription_stream<T>(
+        &self,
+        subscription: Subscription,
+        extract: fn(Event) -> Option<T>,
+    ) -> Result<impl Stream<Item = Result<StreamItem<T>, Error>>, Error> {
+        let mut connection = self.connect().await?;
+        connection.subscribe(&subscription).await?;
+
+        Ok(connection.into_stream().filter_map(move |event| {
+            let item = match event {
+                Ok(Event::Error { message }) => Some(Err(Error::UnexpectedResponse(message))),
+                Ok(Event::Reconnected { attempts }) => {
+                    Some(Ok(StreamItem::Reconnected { attempts }))
+                }
+                Ok(Event::StaleFeed { silent_for }) => {
+                    Some(Ok(StreamItem::StaleFeed { silent_for }))
+                }
+                Ok(event) if event.coin_pair() == Some(&subscription.id) => {
+                    extract(event).map(|data| Ok(StreamItem::Data(data)))
+                }
+                Ok(_) => None,
+                Err(e) => Some(Err(e)),
+            };
+            async move { item }
+        }))
+    }
+}
+
+/// Public: An open connection to the websocket feed
+pub struct Connection {
+    url: String,
+    stream: WebSocketStream<MaybeTlsStream<TcpStream>>,
+    /// Replayed after reconnecting
+    subscriptions: Vec<Subscription>,
+    reconnect: Option<RetryPolicy>,
+    /// Attempt of the reconnection in progress, 0 while connected. Kept
+    /// here, with `reopened` and `pending_replay`, so a reconnection
+    /// interrupted by dropping `next_event` (e.g. in `tokio::select!`)
+    /// resumes on the next call instead of losing subscriptions.
+    reconnect_attempt: u32,
+    reopened: bool,
+    pending_replay: Vec<Subscription>,
+    heartbeat: Option<Duration>,
+    stale_after: Option<Duration>,
+    last_ping: Instant,
+    last_message: Instant,
+    stale_reported: bool,
+}
+
+impl Connection {
+    /// Public: Connect to the feed at `url`, usually `WS_URL`
+    pub async fn connect(url: &str) -> Result<Self, Error> {
+        let (stream, _) = tokio_tungstenite::connect_async(url).await?;
+        Ok(Connection {
+            url: url.to_string(),
+            stream,
+            subscriptions: Vec::new(),
+            reconnect: None,
+            reconnect_attempt: 0,
+            reopened: false,
+            pending_replay: Vec::new(),
+            heartbeat: None,
+            stale_after: None,
+            last_ping: Instant::now(),
+            last_message: Instant::now(),
+            stale_reported: false,
+        })
+    }
+
+    /// Public: Reconnect with backoff when the connection drops, replaying
+    /// the active subscriptions and reporting it with `Event::Reconnected`
+    pub fn with_reconnect(mut self, policy: RetryPolicy) -> Self {
+        self.reconnect = Some(policy);
+        self
+    }
+
+    /// Public: Ping the server every `interval`, so an idle connection
+    /// still gets messages
+    pub fn with_heartbeat(mut self, interval: Duration) -> Self {
+        self.heartbeat = Some(interval);
+        self
+    }
+
+    /// Public: Report `Event::StaleFeed` when no message arrives for
+    /// `timeout`. Pair it with `with_heartbeat` at a shorter interval so a
+    /// quiet market isn't taken for a dead feed.
+    pub fn with_stale_after(mut self, timeout: Duration) -> Self {
+        self.stale_after = Some(timeout);
+        self
+    }
+
+    pub async fn subscribe(&mut self, subscription: &Subscription) -> Result<(), Error> {
+        self.send(&Command::Subscribe { subscription }).await?;
+        if !self.subscriptions.contains(subscription) {
+            self.subscriptions.push(subscription.clone());
+        }
+        self.pending_replay.retain(|s| s != subscription);
+        Ok(())
+    }
+
+    pub async fn unsubscribe(&mut self, subscription: &Subscription) -> Result<(), Error> {
+        self.send(&Command::Unsubscribe { subscription }).await?;
+        self.subscriptions.retain(|s| s != subscription);
+        self.pending_replay.retain(|s| s != subscription);
+        Ok(())
+    }
+
+    /// Subscriptions replayed on reconnection
+    pub fn subscriptions(&self) -> &[Subscription] {
+        &self.subscriptions
+    }
+
+    /// Ask the server for an `Event::Pong`
+    pub async fn ping(&mut self) -> Result<(), Error> {
+        self.send(&Command::Ping).await
+    }
+
+    /// The next event pushed by the server, `None` once the connection is
+    /// closed and won't be reconnected. Cancel safe: dropping the future
+    /// loses no event, and an interrupted reconnection resumes on the next
+    /// call.
+    pub async fn next_event(&mut self) -> Option<Result<Event, Error>> {
+        if self.reconnect_attempt > 0 {
+            return self.reconnect().await;
+        }
+
+        loop {
+            let message = match self.deadline() {
+                Some(deadline) => match timeout_at(deadline, self.stream.next()).await {
+                    Ok(message) => message,
+                    Err(_) => match self.on_deadline().await {
+                        Some(event) => return Some(event),
+                        None => continue,
+                    },
+                },
+                None => self.stream.next().await,
+            };
+            if message.is_some() {
+                self.last_message = Instant::now();
+                self.stale_reported = false;
+            }
+
+            let text = match message {
+                Some(Ok(Message::Text(text))) => text,
+                // Control frames are answered by tungstenite itself
+                Some(Ok(Message::Ping(_))) | Some(Ok(Message::Pong(_))) => continue,
+                Some(Ok(Message::Binary(_))) | Some(Ok(Message::Frame(_))) => continue,
+                Some(Ok(Message::Close(_))) | None => return self.disconnected(None).await,
+                Some(Err(e)) => return self.disconnected(Some(e.into())).await,
+            };
+
+            let decoded = serde_json::from_str::<RawEvent>(&text).and_then(RawEvent::decode);
+            match decoded {
+                Ok(Some(event)) => return Some(Ok(event)),
+                Ok(None) => continue,
+                Err(e) => return Some(Err(Error::UnexpectedResponse(e.to_string()))),
+            }
+        }
+    }
+
+    /// When the next ping is due or the feed turns stale, whichever is first
+    fn deadline(&self) -> Option<Instant> {
+        let ping = self.heartbeat.map(|interval| self.last_ping + interval);
+        let stale = self
+            .stale_after
+            .filter(|_| !self.stale_reported)
+            .map(|timeout| self.last_message + timeout);
+
+        match (ping, stale) {
+            (Some(ping), Some(stale)) => Some(ping.min(stale)),
+            (ping, stale) => ping.or(stale),
+        }
+    }
+
+    async fn on_deadline(&mut self) -> Option<Result<Event, Error>> {
+        let now = Instant::now();
+
+        if let Some(timeout) = self.stale_after {
+            let silent_for = now - self.last_message;
+            if !self.stale_reported && silent_for >= timeout {
+                self.stale_reported = true;
+                return Some(Ok(Event::StaleFeed { silent_for }));
+            }
+        }
+
+        if let Some(interval) = self.heartbeat {
+            if now - self.last_ping >= interval {
+                self.last_ping = now;
+                if let Err(e) = self.ping().await {
+                    return Some(Err(e));
+                }
+            }
+        }
+
+        None
+    }
+
+    async fn disconnected(&mut self, error: Option<Error>) -> Option<Result<Event, Error>> {
+        if self.reconnect.is_none() {
+            return error.map(Err);
+        }
+
+        self.reconnect_attempt = 1;
+        self.reconnect().await
+    }
+
+    /// Connect again with backoff and replay the subscriptions, reporting
+    /// `Event::Reconnected` or the last error once the attempts run out
+    async fn reconnect(&mut self) -> Option<Result<Event, Error>> {
+        let policy = self.reconnect.clone()?;
+
+        loop {
+            let mut result = Ok(());
+            if !self.reopened {
+                result = self.reopen().await;
+            }
+            if result.is_ok() {
+                result = self.replay().await;
+            }
+
+            match result {
+                Ok(()) => {
+                    let attempts = self.reconnect_attempt;
+                    self.reconnect_attempt = 0;
+                    self.reopened = false;
+                    return Some(Ok(Event::Reconnected { attempts }));
+                }
+                Err(e) if self.reconnect_attempt >= policy.max_attempts => {
+                    self.reconnect_attempt = 0;
+                    self.reopened = false;
+                    return Some(Err(e));
+                }
+                Err(_) => {
+                    self.reopened = false;
+                    let exponent = (self.reconnect_attempt - 1).min(16);
+                    let backoff = policy
+                        .initial_backoff
+                        .checked_mul(2u32.pow(exponent))
+                        .map_or(policy.max_backoff, |backoff| {
+                            backoff.min(policy.max_backoff)
+                        });
+                    tokio::time::sleep(backoff).await;
+                    self.reconnect_attempt += 1;
+                }
+            }
+        }
+    }
+
+    /// Connect again, queueing the subscriptions to replay
+    async fn reopen(&mut self) -> Result<(), Error> {
+        let (stream, _) = tokio_tungstenite::connect_async(self.url.as_str()).await?;
+        self.stream = stream;
+        self.last_message = Instant::now();
+        self.stale_reported = false;
+        self.pending_replay = self.subscriptions.clone();
+        self.reopened = true;
+        Ok(())
+    }
+
+    /// Send the subscriptions still to replay, dropping each once it's sent
+    async fn replay(&mut self) -> Result<(), Error> {
+        while let Some(subscription) = self.pending_replay.first().cloned() {
+            self.send(&Command::Subscribe {
+                subscription: &subscription,
+            })
+            .await?;
+            self.pending_replay.remove(0);
+        }
+        Ok(())
+    }
+
+    /// Every event pushed by the server, ending when the connection closes
+    /// and won't be reconnected
+    pub fn into_stream(self) -> impl Stream<Item = Result<Event, Error>> {
+        stream::unfold(self, |mut connection| async move {
+            let event = connection.next_event().await?;
+            Some((event, connection))
+        })
+    }
+
+    pub async fn close(mut self) -> Result<(), Error> {
+        self.stream.close(None).await?;
+        Ok(())
+    }
+
+    async fn send(&mut self, command: &Command<'_>) -> Result<(), Error> {
+        let text = serde_json::to_string(command).unwrap();
+        self.stream.send(Message::Text(text)).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+    use tokio::time::sleep;
+    use tokio_tungstenite::accept_async;
+
+    type ServerStream = WebSocketStream<TcpStream>;
+
+    /// A local feed and its url
+    async fn listen() -> (TcpListener, String) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        (listener, url)
+    }
+
+    async fn accept(listener: &TcpListener) -> ServerStream {
+        let (stream, _) = listener.accept().await.unwrap();
+        accept_async(stream).await.unwrap()
+    }
+
+    /// The next text message the server receives
+    async fn received(stream: &mut ServerStream) -> serde_json::Value {
+        loop {
+            match stream.next().await.unwrap().unwrap() {
+                Message::Text(text) => return serde_json::from_str(&text).unwrap(),
+                _ => continue,
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn silent_feeds_are_reported_stale_once() {
+        let (listener, url) = listen().await;
+        let server = tokio::spawn(async move {
+            let mut stream = accept(&listener).await;
+            // Holds the connection open without sending anything
+            while stream.next().await.is_some() {}
+        });
+
+        let mut connection = Connection::connect(&url)
+            .await
+            .unwrap()
+            .with_stale_after(Duration::from_millis(50));
+
+        match connection.next_event().await {
+            Some(Ok(Event::StaleFeed { silent_for })) => {
+                assert!(silent_for >= Duration::from_millis(50))
+            }
+            other => panic!("expected a stale feed, got {:?}", other),
+        }
+        let again = tokio::time::timeout(Duration::from_millis(150), connection.next_event());
+        assert!(again.await.is_err(), "a stale feed is reported once");
+
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn heartbeats_keep_the_feed_fresh() {
+        let (listener, url) = listen().await;
+        let server = tokio::spawn(async move {
+            let mut stream = accept(&listener).await;
+            let mut pings = 0;
+            while pings < 3 {
+                if received(&mut stream).await["type"] == "ping" {
+                    pings += 1;
+                    let pong = r#"{"type":"pong"}"#.to_string();
+                    stream.send(Message::Text(pong)).await.unwrap();
+                }
+            }
+        });
+
+        let mut connection = Connection::connect(&url)
+            .await
+            .unwrap()
+            .with_heartbeat(Duration::from_millis(30))
+            .with_stale_after(Duration::from_millis(100));
+
+        for _ in 0..3 {
+            match connection.next_event().await {
+                Some(Ok(Event::Pong)) => {}
+                other => panic!("expected a pong, got {:?}", other),
+            }
+        }
+
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn subscription_streams_report_stale_feeds() {
+        let (listener, url) = listen().await;
+        let server = tokio::spawn(async move {
+            let mut stream = accept(&listener).await;
+            assert_eq!(received(&mut stream).await["type"], "subscribe");
+            let trade = r#"{"type":"trade","id":"BRLBTC","data":{"tid":1,"date":1600000000,"type":"buy","price":"100.5","amount":"0.1"}}"#;
+            stream.send(Message::Text(trade.to_string())).await.unwrap();
+            while stream.next().await.is_some() {}
+        });
+
+        let client = Client::new(url).with_stale_after(Duration::from_millis(50));
+        let mut trades = Box::pin(client.trade_stream("BRLBTC").await.unwrap());
+
+        match trades.next().await {
+            Some(Ok(StreamItem::Data(trade))) => assert_eq!(trade.tid, 1),
+            other => panic!("expected a trade, got {:?}", other),
+        }
+        match trades.next().await {
+            Some(Ok(StreamItem::StaleFeed { silent_for })) => {
+                assert!(silent_for >= Duration::from_millis(50))
+            }
+            other => panic!("expected a stale feed, got {:?}", other),
+        }
+
+        server.abort();
+    }
+
+    fn quick_retries(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            initial_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_millis(50),
+        }
+    }
+
+    const TRADE: &str = r#"{"type":"trade","id":"BRLBTC","data":{"tid":7,"date":1600000000,"type":"sell","price":"100","amount":"1"}}"#;
+
+    /// Read the subscription the client sends on a new connection and answer
+    /// it with a trade
+    async fn expect_subscription(stream: &mut ServerStream) {
+        let subscribe = received(stream).await;
+        assert_eq!(subscribe["type"], "subscribe");
+        assert_eq!(subscribe["subscription"]["id"], "BRLBTC");
+        stream.send(Message::Text(TRADE.to_string())).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn dropped_connections_are_reopened_with_their_subscriptions() {
+        let (listener, url) = listen().await;
+        let server = tokio::spawn(async move {
+            let mut first = accept(&listener).await;
+            assert_eq!(received(&mut first).await["type"], "subscribe");
+            drop(first);
+
+            let mut second = accept(&listener).await;
+            expect_subscription(&mut second).await;
+            while second.next().await.is_some() {}
+        });
+
+        let mut connection = Connection::connect(&url)
+            .await
+            .unwrap()
+            .with_reconnect(quick_retries(3));
+        connection
+            .subscribe(&Subscription::trades("BRLBTC"))
+            .await
+            .unwrap();
+
+        match connection.next_event().await {
+            Some(Ok(Event::Reconnected { attempts: 1 })) => {}
+            other => panic!("expected a reconnection, got {:?}", other),
+        }
+        match connection.next_event().await {
+            Some(Ok(Event::Trade { trade, .. })) => assert_eq!(trade.tid, 7),
+            other => panic!(
+                "expected the replayed subscription's trade, got {:?}",
+                other
+            ),
+        }
+
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn interrupted_reconnections_resume_on_the_next_call() {
+        let (listener, url) = listen().await;
+        let server = tokio::spawn(async move {
+            let mut first = accept(&listener).await;
+            assert_eq!(received(&mut first).await["type"], "subscribe");
+            drop(first);
+
+            // Stall the handshake past the caller's timeout, which drops the
+            // reconnection midway
+            let (stalled, _) = listener.accept().await.unwrap();
+            sleep(Duration::from_millis(200)).await;
+            let _ = accept_async(stalled).await;
+
+            let mut resumed = accept(&listener).await;
+            expect_subscription(&mut resumed).await;
+            while resumed.next().await.is_some() {}
+        });
+
+        let mut connection = Connection::connect(&url)
+            .await
+            .unwrap()
+            .with_reconnect(quick_retries(3));
+        connection
+            .subscribe(&Subscription::trades("BRLBTC"))
+            .await
+            .unwrap();
+
+        tokio::select! {
+            event = connection.next_event() => panic!("expected a timeout, got {:?}", event),
+            _ = sleep(Duration::from_millis(100)) => {}
+        }
+
+        match connection.next_event().await {
+            Some(Ok(Event::Reconnected { .. })) => {}
+            other => panic!("expected a reconnection, got {:?}", other),
+        }
+        match connection.next_event().await {
+            Some(Ok(Event::Trade { trade, .. })) => assert_eq!(trade.tid, 7),
+            other => panic!(
+                "expected the replayed subscription's trade, got {:?}",
+                other
+            ),
+        }
+        assert_eq!(connection.subscriptions(), [Subscription::trades("BRLBTC")]);
+
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn connection_errors_reach_subscribers() {
+        let (listener, url) = listen().await;
+        let server = tokio::spawn(async move {
+            let mut stream = accept(&listener).await;
+            assert_eq!(received(&mut stream).await["type"], "subscribe");
+            stream.send(Message::Text(TRADE.to_string())).await.unwrap();
+            // Nothing listens anymore, so reconnecting fails
+            drop(listener);
+        });
+
+        let connection = Connection::connect(&url)
+            .await
+            .unwrap()
+            .with_reconnect(quick_retries(1));
+        let manager = SubscriptionManager::spawn(connection);
+        let mut subscriber = manager
+            .subscribe(Subscription::trades("BRLBTC"))
+            .await
+            .unwrap();
+
+        match subscriber.recv().await {
+            Some(Ok(Event::Trade { trade, .. })) => assert_eq!(trade.tid, 7),
+            other => panic!("expected a trade, got {:?}", other),
+        }
+        match subscriber.recv().await {
+            Some(Err(_)) => {}
+            other => panic!("expected the reconnection error, got {:?}", other),
+        }
+
+        server.await.unwrap();
+    }
+}